@@ -0,0 +1,2 @@
+ALTER TABLE posts ADD COLUMN parent_id UUID REFERENCES posts(id) ON DELETE CASCADE;
+CREATE INDEX idx_posts_parent_id ON posts(parent_id);
@@ -0,0 +1,2 @@
+CREATE INDEX idx_posts_search ON posts
+USING GIN (to_tsvector('english', title || ' ' || content));
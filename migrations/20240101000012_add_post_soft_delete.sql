@@ -0,0 +1,9 @@
+ALTER TABLE posts
+ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_posts_not_deleted ON posts(created_at DESC) WHERE deleted_at IS NULL;
+
+ALTER TABLE users
+ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT false;
+
+UPDATE users SET is_admin = true WHERE username = 'admin';
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS auth_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    username VARCHAR(255) NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    ip TEXT NOT NULL,
+    user_agent TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_auth_events_created_at ON auth_events(created_at DESC);
+CREATE INDEX idx_auth_events_user_id ON auth_events(user_id);
@@ -0,0 +1,4 @@
+ALTER TABLE posts
+ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX idx_posts_pinned_created_at ON posts(pinned DESC, created_at DESC);
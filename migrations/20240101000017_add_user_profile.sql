@@ -0,0 +1,3 @@
+ALTER TABLE users
+ADD COLUMN display_name VARCHAR(50),
+ADD COLUMN bio TEXT;
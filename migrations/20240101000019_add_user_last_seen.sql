@@ -0,0 +1,3 @@
+-- When the user last loaded a post listing; NULL until their first visit,
+-- so every post counts as unread for a brand new account.
+ALTER TABLE users ADD COLUMN last_seen_at TIMESTAMPTZ;
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    reporter_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    reporter_ip TEXT NOT NULL,
+    reason VARCHAR(500) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved BOOLEAN NOT NULL DEFAULT false
+);
+
+CREATE INDEX idx_reports_unresolved ON reports(created_at DESC) WHERE NOT resolved;
+CREATE INDEX idx_reports_post_id ON reports(post_id);
@@ -0,0 +1,7 @@
+-- Human-readable post URLs (/p/:slug). Top-level posts get a slug derived
+-- from the title when created; replies have none. Slugs never change, so
+-- shared links keep working after an edit. Existing posts are given theirs
+-- at startup by db::backfill_post_slugs, which uses the same rules as new
+-- posts (Postgres regex classes depend on the database locale).
+ALTER TABLE posts ADD COLUMN slug TEXT;
+CREATE UNIQUE INDEX idx_posts_slug ON posts(slug);
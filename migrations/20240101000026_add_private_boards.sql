@@ -0,0 +1,22 @@
+ALTER TABLE boards ADD COLUMN visibility VARCHAR(20) NOT NULL DEFAULT 'public'
+    CHECK (visibility IN ('public', 'private'));
+
+CREATE TABLE IF NOT EXISTS board_members (
+    board_id UUID NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (board_id, user_id)
+);
+
+CREATE INDEX idx_board_members_user_id ON board_members(user_id);
+
+-- Whether `viewer` (NULL for anonymous readers) may read posts filed under
+-- `board` (NULL for unfiled posts). Every query that lists or shows posts
+-- filters on this, so private boards stay hidden in one consistent way.
+CREATE OR REPLACE FUNCTION board_visible_to(board UUID, viewer UUID) RETURNS BOOLEAN
+LANGUAGE sql STABLE AS $$
+    SELECT board IS NULL
+        OR EXISTS (SELECT 1 FROM boards WHERE id = board AND visibility = 'public')
+        OR EXISTS (SELECT 1 FROM board_members WHERE board_id = board AND user_id = viewer)
+        OR EXISTS (SELECT 1 FROM users WHERE id = viewer AND is_admin)
+$$;
@@ -0,0 +1,4 @@
+-- When a user was shown the one-time welcome banner. NULL means it is still
+-- due. Accounts that predate the banner count as already welcomed.
+ALTER TABLE users ADD COLUMN welcomed_at TIMESTAMPTZ;
+UPDATE users SET welcomed_at = created_at;
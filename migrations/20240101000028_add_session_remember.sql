@@ -0,0 +1,4 @@
+-- Whether the session was started with "remember me". Sessions without it
+-- end a short while after sign-in however active they are. Sessions from
+-- before this column keep the longer lifetime they were created with.
+ALTER TABLE sessions ADD COLUMN remember BOOLEAN NOT NULL DEFAULT true;
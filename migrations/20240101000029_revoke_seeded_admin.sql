@@ -0,0 +1,4 @@
+-- Admin rights now come from ADMIN_USERNAMES, applied at every startup. The
+-- seeded `admin` account has a published password, so it loses the grant
+-- migration 012 gave it; list it there explicitly to keep it an admin.
+UPDATE users SET is_admin = false WHERE username = 'admin';
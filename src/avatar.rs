@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Identicon grid width and height, in cells.
+const GRID: usize = 5;
+
+/// Generic silhouette shown when avatars are disabled.
+pub const PLACEHOLDER_SVG: &str = concat!(
+    r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 5 5">"##,
+    r##"<rect width="5" height="5" fill="#d1d5db"/>"##,
+    r##"<circle cx="2.5" cy="1.9" r="1" fill="#9ca3af"/>"##,
+    r##"<path d="M0.8 5a1.7 1.7 0 0 1 3.4 0z" fill="#9ca3af"/>"##,
+    "</svg>"
+);
+
+/// Where avatar images come from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AvatarProvider {
+    /// gravatar.com, keyed by the MD5 of the email address.
+    #[default]
+    Gravatar,
+    /// Identicons generated from the username and served by this instance.
+    Local,
+    /// The same placeholder for everyone.
+    Disabled,
+}
+
+impl FromStr for AvatarProvider {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gravatar" => Ok(Self::Gravatar),
+            "local" => Ok(Self::Local),
+            "disabled" => Ok(Self::Disabled),
+            other => Err(format!("unknown avatar provider: {}", other)),
+        }
+    }
+}
+
+/// Image URL for a user's avatar at `size` pixels square.
+pub fn avatar_url(provider: AvatarProvider, email: &str, username: &str, size: u32) -> String {
+    match provider {
+        AvatarProvider::Gravatar => {
+            let email_hash = format!("{:x}", md5::compute(email.trim().to_lowercase().as_bytes()));
+            format!(
+                "https://www.gravatar.com/avatar/{}?s={}&d=identicon",
+                email_hash, size
+            )
+        }
+        AvatarProvider::Local => format!("/avatar/{}.svg", encode_path_segment(username)),
+        AvatarProvider::Disabled => "/avatar/placeholder.svg".to_string(),
+    }
+}
+
+/// Percent-encodes everything outside RFC 3986's unreserved set, since
+/// usernames may contain any character.
+fn encode_path_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+/// A symmetric 5x5 identicon for `username`. The same name always yields
+/// the same image: colour and cells both come from `SHA-256(username)`.
+pub fn identicon_svg(username: &str) -> String {
+    let digest = Sha256::digest(username.as_bytes());
+    let hue = u16::from_be_bytes([digest[0], digest[1]]) % 360;
+    let colour = format!("hsl({}, 55%, 50%)", hue);
+
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {grid} {grid}" shape-rendering="crispEdges"><rect width="{grid}" height="{grid}" fill="#f3f4f6"/>"##,
+        grid = GRID
+    );
+    // Only the left half and the middle column are drawn from the hash; the
+    // right half mirrors them.
+    let half = GRID.div_ceil(2);
+    for row in 0..GRID {
+        for col in 0..half {
+            let bit = row * half + col;
+            if digest[2 + bit / 8] & (1 << (bit % 8)) == 0 {
+                continue;
+            }
+            for x in [col, GRID - 1 - col] {
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="1" height="1" fill="{}"/>"#,
+                    x, row, colour
+                );
+                if x == GRID - 1 - x {
+                    break;
+                }
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identicon_is_deterministic() {
+        assert_eq!(identicon_svg("alice"), identicon_svg("alice"));
+        assert_ne!(identicon_svg("alice"), identicon_svg("bob"));
+        assert!(identicon_svg("alice").starts_with("<svg "));
+        assert!(identicon_svg("alice").ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_identicon_is_mirrored() {
+        let svg = identicon_svg("carol");
+        for row in 0..GRID {
+            for col in 0..GRID {
+                let cell = format!(r#"x="{}" y="{}""#, col, row);
+                let mirror = format!(r#"x="{}" y="{}""#, GRID - 1 - col, row);
+                assert_eq!(svg.contains(&cell), svg.contains(&mirror));
+            }
+        }
+    }
+
+    #[test]
+    fn test_avatar_url_follows_provider() {
+        let gravatar = avatar_url(AvatarProvider::Gravatar, " Alice@Example.com ", "alice", 48);
+        assert_eq!(
+            gravatar,
+            format!(
+                "https://www.gravatar.com/avatar/{:x}?s=48&d=identicon",
+                md5::compute("alice@example.com")
+            )
+        );
+        assert_eq!(
+            avatar_url(AvatarProvider::Local, "", "al ice/é", 48),
+            "/avatar/al%20ice%2F%C3%A9.svg"
+        );
+        assert_eq!(
+            avatar_url(AvatarProvider::Disabled, "", "alice", 48),
+            "/avatar/placeholder.svg"
+        );
+    }
+
+    #[test]
+    fn test_parses_provider_names() {
+        assert_eq!("Local".parse(), Ok(AvatarProvider::Local));
+        assert_eq!("disabled".parse(), Ok(AvatarProvider::Disabled));
+        assert!("robohash".parse::<AvatarProvider>().is_err());
+    }
+}
@@ -1,4 +1,33 @@
+use serde::Deserialize;
 use std::env;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::auth::PasswordParams;
+use crate::avatar::AvatarProvider;
+use crate::Error;
+
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+const DB_MAX_CONNECTIONS_RANGE: RangeInclusive<u32> = 1..=1000;
+const DB_ACQUIRE_TIMEOUT_RANGE: RangeInclusive<u64> = 1..=600;
+const DB_CONNECT_ATTEMPTS_RANGE: RangeInclusive<u32> = 1..=100;
+const DB_CONNECT_MAX_DELAY_RANGE: RangeInclusive<u64> = 1..=600;
+const SSH_INACTIVITY_TIMEOUT_RANGE: RangeInclusive<u64> = 1..=7 * 24 * 3600;
+const SSH_IDLE_LOGOUT_RANGE: RangeInclusive<u64> = 0..=7 * 24 * 3600;
+const SSH_MAX_SESSIONS_RANGE: RangeInclusive<usize> = 1..=10_000;
+const REGISTRATION_POW_BITS_RANGE: RangeInclusive<u32> = 0..=32;
+const POST_RATE_LIMIT_RANGE: RangeInclusive<usize> = 1..=10_000;
+const POST_RATE_WINDOW_RANGE: RangeInclusive<u64> = 1..=24 * 3600;
+const SESSION_LIFETIME_RANGE: RangeInclusive<u64> = 60..=365 * 24 * 3600;
+const SESSION_CLEANUP_RANGE: RangeInclusive<u64> = 60..=86_400;
+const PAGE_SIZE_RANGE: RangeInclusive<usize> = 1..=1000;
+// Argon2 itself needs at least 8 KiB per lane; 4 GiB is far beyond sane.
+const ARGON2_MEMORY_RANGE: RangeInclusive<u32> = 8..=4 * 1024 * 1024;
+const ARGON2_ITERATIONS_RANGE: RangeInclusive<u32> = 1..=100;
+const ARGON2_PARALLELISM_RANGE: RangeInclusive<u32> = 1..=64;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -6,25 +35,472 @@ pub struct Config {
     pub web_port: u16,
     pub ssh_port: u16,
     pub ssh_host_key_path: String,
+    pub session_cleanup_secs: u64,
+    /// Web sessions unused for this long expire.
+    pub session_idle_timeout_secs: u64,
+    /// Web sessions expire this long after sign-in, however active.
+    pub session_max_age_secs: u64,
+    pub db_max_connections: u32,
+    pub db_acquire_timeout_secs: u64,
+    /// Tries at reaching the database on startup before giving up, waiting
+    /// longer after each failure, up to `db_connect_max_delay_secs`.
+    pub db_connect_attempts: u32,
+    pub db_connect_max_delay_secs: u64,
+    pub ssh_inactivity_timeout_secs: u64,
+    /// SSH sessions with no keypresses for this long are told they timed
+    /// out and closed, even if the connection itself is kept alive. 0 turns
+    /// this off.
+    pub ssh_idle_logout_secs: u64,
+    /// Concurrent SSH sessions allowed before new ones are turned away.
+    pub ssh_max_sessions: usize,
+    /// Banner shown above the SSH login prompt; a missing file is ignored.
+    pub ssh_motd_path: String,
+    /// SSH username that logs in as a read-only guest without a password.
+    /// `None` disables guest access.
+    pub guest_username: Option<String>,
+    /// Origins allowed to make credentialed cross-origin requests. Empty
+    /// means cross-origin access is disabled.
+    pub allowed_origins: Vec<String>,
+    /// Usernames with admin rights, granted at startup and revoked from
+    /// everyone not listed. Empty means nobody is an admin.
+    pub admin_usernames: Vec<String>,
+    /// Leading zero bits the registration proof-of-work must have; 0 only
+    /// checks that the challenge is fresh.
+    pub registration_pow_bits: u32,
+    /// Posts (drafts and replies included) one user may create per
+    /// `post_rate_window_secs`.
+    pub post_rate_limit: usize,
+    pub post_rate_window_secs: u64,
+    /// Where avatar images come from: gravatar, local identicons, or a
+    /// placeholder for everyone.
+    pub avatar_provider: AvatarProvider,
+    /// Whether the web server sits behind a reverse proxy whose
+    /// `X-Forwarded-For` header names the real client.
+    pub trust_proxy: bool,
+    /// Replaces the default `Content-Security-Policy`, e.g. to allow a
+    /// self-hosted avatar server.
+    pub content_security_policy: Option<String>,
+    /// Shown once on the index to each newly registered user; `None` skips
+    /// the welcome.
+    pub welcome_message: Option<String>,
+    /// Posts per page of every listing: the web index, board and tag pages,
+    /// the SSH post list, and the API when no `per_page` is given.
+    pub default_page_size: usize,
+    /// Largest `per_page` the API accepts; bigger requests are capped.
+    pub max_page_size: usize,
+    /// Cost of new password hashes. Older, weaker hashes are upgraded on
+    /// the owner's next successful login.
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+    /// Start in read-only mode. Admins can switch it at runtime through
+    /// `POST /admin/maintenance`.
+    pub maintenance_mode: bool,
+}
+
+/// How log lines are written, chosen with `LOG_FORMAT`.
+///
+/// Accepted values (case-insensitive):
+/// - `pretty` (the default): human-readable lines for local development.
+/// - `json`: one JSON object per line with the timestamp, level, target and
+///   fields, for log aggregators.
+///
+/// `RUST_LOG` filters events the same way in both formats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            other => Err(format!("unknown log format: {}", other)),
+        }
+    }
+}
+
+impl LogFormat {
+    /// Reads `LOG_FORMAT`. Logging is set up before `config.toml` is loaded,
+    /// so unlike other settings this one comes from the environment only.
+    pub fn from_env() -> crate::Result<Self> {
+        Ok(env_or("LOG_FORMAT", None)?.unwrap_or_default())
+    }
+}
+
+/// On-disk layout of `config.toml`. Every field is optional so a file only
+/// needs to contain the values it wants to set.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    maintenance_mode: Option<bool>,
+    database: DatabaseSection,
+    web: WebSection,
+    ssh: SshSection,
+    sessions: SessionsSection,
+    pagination: PaginationSection,
+    passwords: PasswordsSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DatabaseSection {
+    url: Option<String>,
+    max_connections: Option<u32>,
+    acquire_timeout_secs: Option<u64>,
+    connect_attempts: Option<u32>,
+    connect_max_delay_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WebSection {
+    port: Option<u16>,
+    allowed_origins: Option<Vec<String>>,
+    admin_usernames: Option<Vec<String>>,
+    registration_pow_bits: Option<u32>,
+    post_rate_limit: Option<usize>,
+    post_rate_window_secs: Option<u64>,
+    avatar_provider: Option<AvatarProvider>,
+    trust_proxy: Option<bool>,
+    content_security_policy: Option<String>,
+    welcome_message: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SshSection {
+    port: Option<u16>,
+    host_key_path: Option<String>,
+    inactivity_timeout_secs: Option<u64>,
+    idle_logout_secs: Option<u64>,
+    max_sessions: Option<usize>,
+    motd_path: Option<String>,
+    guest_username: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SessionsSection {
+    cleanup_secs: Option<u64>,
+    idle_timeout_secs: Option<u64>,
+    max_age_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PaginationSection {
+    default_page_size: Option<usize>,
+    max_page_size: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PasswordsSection {
+    argon2_memory_kib: Option<u32>,
+    argon2_iterations: Option<u32>,
+    argon2_parallelism: Option<u32>,
+}
+
+/// Reads `key` from the environment, falling back to the file value.
+fn env_or<T: FromStr>(key: &str, file_value: Option<T>) -> crate::Result<Option<T>> {
+    match env::var(key) {
+        Ok(raw) => raw
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::Internal(format!("{} has an invalid value: {:?}", key, raw))),
+        Err(_) => Ok(file_value),
+    }
+}
+
+/// Reads a comma-separated list from `key`, falling back to the file value.
+fn env_list_or(key: &str, file_value: Option<Vec<String>>) -> Vec<String> {
+    let values = match env::var(key) {
+        Ok(raw) => raw.split(',').map(str::to_string).collect(),
+        Err(_) => file_value.unwrap_or_default(),
+    };
+    values
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Rejects values outside `range`, which would otherwise give a useless pool or server.
+fn in_range<T: PartialOrd + Display>(
+    key: &str,
+    value: T,
+    range: RangeInclusive<T>,
+) -> crate::Result<T> {
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(Error::Internal(format!(
+            "{} must be between {} and {}, got {}",
+            key,
+            range.start(),
+            range.end(),
+            value
+        )))
+    }
 }
 
 impl Config {
+    /// Loads `config.toml` (or the file named by `IRON_BBS_CONFIG`) when it
+    /// exists, otherwise configures from environment variables alone.
+    pub fn load() -> crate::Result<Self> {
+        dotenvy::dotenv().ok();
+
+        let path = env::var("IRON_BBS_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        let path = Path::new(&path);
+
+        if path.exists() {
+            tracing::info!("Loading configuration from {}", path.display());
+            Self::from_file(path)
+        } else {
+            Self::from_env()
+        }
+    }
+
+    /// Parses a TOML config file. Environment variables override file values.
+    pub fn from_file(path: &Path) -> crate::Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            Error::Internal(format!("Failed to read config {}: {}", path.display(), e))
+        })?;
+        let file: FileConfig = toml::from_str(&contents)
+            .map_err(|e| Error::Internal(format!("Invalid config {}: {}", path.display(), e)))?;
+
+        Self::resolve(file)
+    }
+
     pub fn from_env() -> crate::Result<Self> {
         dotenvy::dotenv().ok();
 
-        Ok(Self {
-            database_url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
-            web_port: env::var("WEB_PORT")
-                .unwrap_or_else(|_| "3000".to_string())
-                .parse()
-                .expect("WEB_PORT must be a valid port number"),
-            ssh_port: env::var("SSH_PORT")
-                .unwrap_or_else(|_| "2222".to_string())
-                .parse()
-                .expect("SSH_PORT must be a valid port number"),
-            ssh_host_key_path: env::var("SSH_HOST_KEY_PATH")
-                .unwrap_or_else(|_| "./ssh_host_key".to_string()),
-        })
+        Self::resolve(FileConfig::default())
+    }
+
+    fn resolve(file: FileConfig) -> crate::Result<Self> {
+        let database_url = env_or("DATABASE_URL", file.database.url)?.ok_or_else(|| {
+            Error::Internal(
+                "DATABASE_URL must be set (or [database] url in config.toml)".to_string(),
+            )
+        })?;
+        let defaults = PasswordParams::default();
+
+        let config = Self {
+            database_url,
+            web_port: env_or("WEB_PORT", file.web.port)?.unwrap_or(3000),
+            ssh_port: env_or("SSH_PORT", file.ssh.port)?.unwrap_or(2222),
+            ssh_host_key_path: env_or("SSH_HOST_KEY_PATH", file.ssh.host_key_path)?
+                .unwrap_or_else(|| "./ssh_host_key".to_string()),
+            session_cleanup_secs: in_range(
+                "SESSION_CLEANUP_SECS",
+                env_or("SESSION_CLEANUP_SECS", file.sessions.cleanup_secs)?.unwrap_or(3600),
+                SESSION_CLEANUP_RANGE,
+            )?,
+            session_idle_timeout_secs: in_range(
+                "SESSION_IDLE_TIMEOUT_SECS",
+                env_or("SESSION_IDLE_TIMEOUT_SECS", file.sessions.idle_timeout_secs)?
+                    .unwrap_or(7 * 24 * 3600),
+                SESSION_LIFETIME_RANGE,
+            )?,
+            session_max_age_secs: in_range(
+                "SESSION_MAX_AGE_SECS",
+                env_or("SESSION_MAX_AGE_SECS", file.sessions.max_age_secs)?
+                    .unwrap_or(30 * 24 * 3600),
+                SESSION_LIFETIME_RANGE,
+            )?,
+            db_max_connections: in_range(
+                "DB_MAX_CONNECTIONS",
+                env_or("DB_MAX_CONNECTIONS", file.database.max_connections)?.unwrap_or(20),
+                DB_MAX_CONNECTIONS_RANGE,
+            )?,
+            db_acquire_timeout_secs: in_range(
+                "DB_ACQUIRE_TIMEOUT_SECS",
+                env_or(
+                    "DB_ACQUIRE_TIMEOUT_SECS",
+                    file.database.acquire_timeout_secs,
+                )?
+                .unwrap_or(30),
+                DB_ACQUIRE_TIMEOUT_RANGE,
+            )?,
+            db_connect_attempts: in_range(
+                "DB_CONNECT_ATTEMPTS",
+                env_or("DB_CONNECT_ATTEMPTS", file.database.connect_attempts)?.unwrap_or(5),
+                DB_CONNECT_ATTEMPTS_RANGE,
+            )?,
+            db_connect_max_delay_secs: in_range(
+                "DB_CONNECT_MAX_DELAY_SECS",
+                env_or(
+                    "DB_CONNECT_MAX_DELAY_SECS",
+                    file.database.connect_max_delay_secs,
+                )?
+                .unwrap_or(30),
+                DB_CONNECT_MAX_DELAY_RANGE,
+            )?,
+            ssh_inactivity_timeout_secs: in_range(
+                "SSH_INACTIVITY_TIMEOUT_SECS",
+                env_or(
+                    "SSH_INACTIVITY_TIMEOUT_SECS",
+                    file.ssh.inactivity_timeout_secs,
+                )?
+                .unwrap_or(3600),
+                SSH_INACTIVITY_TIMEOUT_RANGE,
+            )?,
+            ssh_idle_logout_secs: in_range(
+                "SSH_IDLE_LOGOUT_SECS",
+                env_or("SSH_IDLE_LOGOUT_SECS", file.ssh.idle_logout_secs)?.unwrap_or(900),
+                SSH_IDLE_LOGOUT_RANGE,
+            )?,
+            ssh_max_sessions: in_range(
+                "SSH_MAX_SESSIONS",
+                env_or("SSH_MAX_SESSIONS", file.ssh.max_sessions)?.unwrap_or(100),
+                SSH_MAX_SESSIONS_RANGE,
+            )?,
+            ssh_motd_path: env_or("SSH_MOTD_PATH", file.ssh.motd_path)?
+                .unwrap_or_else(|| "./motd.txt".to_string()),
+            guest_username: guest_username(env_or("SSH_GUEST_USERNAME", file.ssh.guest_username)?),
+            allowed_origins: env_list_or("ALLOWED_ORIGINS", file.web.allowed_origins),
+            admin_usernames: env_list_or("ADMIN_USERNAMES", file.web.admin_usernames),
+            registration_pow_bits: in_range(
+                "REGISTRATION_POW_BITS",
+                env_or("REGISTRATION_POW_BITS", file.web.registration_pow_bits)?.unwrap_or(16),
+                REGISTRATION_POW_BITS_RANGE,
+            )?,
+            post_rate_limit: in_range(
+                "POST_RATE_LIMIT",
+                env_or("POST_RATE_LIMIT", file.web.post_rate_limit)?.unwrap_or(5),
+                POST_RATE_LIMIT_RANGE,
+            )?,
+            post_rate_window_secs: in_range(
+                "POST_RATE_WINDOW_SECS",
+                env_or("POST_RATE_WINDOW_SECS", file.web.post_rate_window_secs)?.unwrap_or(60),
+                POST_RATE_WINDOW_RANGE,
+            )?,
+            avatar_provider: env_or("AVATAR_PROVIDER", file.web.avatar_provider)?
+                .unwrap_or_default(),
+            trust_proxy: env_or("TRUST_PROXY", file.web.trust_proxy)?.unwrap_or(false),
+            content_security_policy: env_or(
+                "CONTENT_SECURITY_POLICY",
+                file.web.content_security_policy,
+            )?
+            .filter(|policy| !policy.trim().is_empty()),
+            welcome_message: env_or("WELCOME_MESSAGE", file.web.welcome_message)?
+                .filter(|message| !message.trim().is_empty()),
+            default_page_size: in_range(
+                "DEFAULT_PAGE_SIZE",
+                env_or("DEFAULT_PAGE_SIZE", file.pagination.default_page_size)?.unwrap_or(25),
+                PAGE_SIZE_RANGE,
+            )?,
+            max_page_size: in_range(
+                "MAX_PAGE_SIZE",
+                env_or("MAX_PAGE_SIZE", file.pagination.max_page_size)?.unwrap_or(100),
+                PAGE_SIZE_RANGE,
+            )?,
+            argon2_memory_kib: in_range(
+                "ARGON2_MEMORY_KIB",
+                env_or("ARGON2_MEMORY_KIB", file.passwords.argon2_memory_kib)?
+                    .unwrap_or(defaults.memory_kib),
+                ARGON2_MEMORY_RANGE,
+            )?,
+            argon2_iterations: in_range(
+                "ARGON2_ITERATIONS",
+                env_or("ARGON2_ITERATIONS", file.passwords.argon2_iterations)?
+                    .unwrap_or(defaults.iterations),
+                ARGON2_ITERATIONS_RANGE,
+            )?,
+            argon2_parallelism: in_range(
+                "ARGON2_PARALLELISM",
+                env_or("ARGON2_PARALLELISM", file.passwords.argon2_parallelism)?
+                    .unwrap_or(defaults.parallelism),
+                ARGON2_PARALLELISM_RANGE,
+            )?,
+            maintenance_mode: env_or("MAINTENANCE_MODE", file.maintenance_mode)?.unwrap_or(false),
+        };
+
+        if config.default_page_size > config.max_page_size {
+            return Err(Error::Internal(format!(
+                "DEFAULT_PAGE_SIZE ({}) must not exceed MAX_PAGE_SIZE ({})",
+                config.default_page_size, config.max_page_size
+            )));
+        }
+
+        if config.argon2_memory_kib < 8 * config.argon2_parallelism {
+            return Err(Error::Internal(format!(
+                "ARGON2_MEMORY_KIB must be at least 8 per lane of ARGON2_PARALLELISM ({} KiB)",
+                8 * config.argon2_parallelism
+            )));
+        }
+
+        Ok(config)
+    }
+
+    /// Catches settings that parse but cannot work, before anything binds or
+    /// connects, so a typo is reported by name instead of as a failure later.
+    pub fn validate(&self) -> crate::Result<()> {
+        if !["postgres://", "postgresql://"]
+            .iter()
+            .any(|scheme| self.database_url.starts_with(scheme))
+        {
+            return Err(Error::Internal(
+                "DATABASE_URL must start with postgres:// or postgresql://".to_string(),
+            ));
+        }
+
+        for (key, port) in [("WEB_PORT", self.web_port), ("SSH_PORT", self.ssh_port)] {
+            if port == 0 {
+                return Err(Error::Internal(format!("{} must not be 0", key)));
+            }
+        }
+        if self.web_port == self.ssh_port {
+            return Err(Error::Internal(format!(
+                "WEB_PORT and SSH_PORT must differ, both are {}",
+                self.web_port
+            )));
+        }
+
+        // A missing key is generated on first start, creating directories as
+        // needed, so the closest existing one has to accept new files.
+        let key_path = Path::new(&self.ssh_host_key_path);
+        if !key_path.exists() {
+            let dir = key_path
+                .ancestors()
+                .skip(1)
+                .map(|dir| {
+                    if dir.as_os_str().is_empty() {
+                        Path::new(".")
+                    } else {
+                        dir
+                    }
+                })
+                .find(|dir| dir.exists())
+                .unwrap_or(Path::new("."));
+            let writable = std::fs::metadata(dir)
+                .map(|meta| meta.is_dir() && !meta.permissions().readonly())
+                .unwrap_or(false);
+            if !writable {
+                return Err(Error::Internal(format!(
+                    "SSH_HOST_KEY_PATH {} does not exist and {} is not a writable directory",
+                    key_path.display(),
+                    dir.display()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn password_params(&self) -> PasswordParams {
+        PasswordParams {
+            memory_kib: self.argon2_memory_kib,
+            iterations: self.argon2_iterations,
+            parallelism: self.argon2_parallelism,
+        }
     }
 
     pub fn web_addr(&self) -> String {
@@ -35,3 +511,261 @@ impl Config {
         format!("0.0.0.0:{}", self.ssh_port)
     }
 }
+
+/// Guests log in as `bbs` unless configured otherwise; an empty name turns
+/// guest access off.
+fn guest_username(value: Option<String>) -> Option<String> {
+    match value {
+        None => Some("bbs".to_string()),
+        Some(name) => {
+            let name = name.trim();
+            (!name.is_empty()).then(|| name.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_file_sections() {
+        let file: FileConfig = toml::from_str(
+            r#"
+            maintenance_mode = true
+
+            [database]
+            url = "postgresql://localhost/test"
+
+            [web]
+            port = 8080
+            welcome_message = "Hello!"
+
+            [ssh]
+            port = 2200
+            host_key_path = "/data/host_key"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            file.database.url.as_deref(),
+            Some("postgresql://localhost/test")
+        );
+        assert_eq!(file.web.port, Some(8080));
+        assert_eq!(file.web.welcome_message.as_deref(), Some("Hello!"));
+        assert_eq!(file.ssh.port, Some(2200));
+        assert_eq!(file.ssh.host_key_path.as_deref(), Some("/data/host_key"));
+        assert_eq!(file.sessions.cleanup_secs, None);
+        assert_eq!(file.maintenance_mode, Some(true));
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        let result = toml::from_str::<FileConfig>("[web]\nprot = 8080\n");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_env_or_reports_invalid_values() {
+        env::set_var("IRON_BBS_TEST_BAD_PORT", "not-a-port");
+
+        let result = env_or::<u16>("IRON_BBS_TEST_BAD_PORT", Some(3000));
+
+        assert!(
+            matches!(result, Err(Error::Internal(msg)) if msg.contains("IRON_BBS_TEST_BAD_PORT"))
+        );
+        env::remove_var("IRON_BBS_TEST_BAD_PORT");
+    }
+
+    #[test]
+    fn test_in_range_rejects_zero_and_absurd_values() {
+        assert_eq!(
+            in_range("DB_MAX_CONNECTIONS", 20, DB_MAX_CONNECTIONS_RANGE).unwrap(),
+            20
+        );
+
+        let zero = in_range("DB_MAX_CONNECTIONS", 0, DB_MAX_CONNECTIONS_RANGE);
+        assert!(matches!(zero, Err(Error::Internal(msg)) if msg.contains("DB_MAX_CONNECTIONS")));
+
+        let huge = in_range(
+            "DB_ACQUIRE_TIMEOUT_SECS",
+            u64::MAX,
+            DB_ACQUIRE_TIMEOUT_RANGE,
+        );
+        assert!(huge.is_err());
+    }
+
+    #[test]
+    fn test_resolve_rejects_zero_pool_size_from_file() {
+        let file: FileConfig = toml::from_str(
+            r#"
+            [database]
+            url = "postgresql://localhost/test"
+            max_connections = 0
+            "#,
+        )
+        .unwrap();
+
+        let result = Config::resolve(file);
+
+        assert!(matches!(result, Err(Error::Internal(msg)) if msg.contains("DB_MAX_CONNECTIONS")));
+    }
+
+    #[test]
+    fn test_resolve_rejects_zero_session_cleanup_interval() {
+        let file: FileConfig = toml::from_str(
+            r#"
+            [database]
+            url = "postgresql://localhost/test"
+
+            [sessions]
+            cleanup_secs = 0
+            "#,
+        )
+        .unwrap();
+
+        let result = Config::resolve(file);
+
+        assert!(
+            matches!(result, Err(Error::Internal(msg)) if msg.contains("SESSION_CLEANUP_SECS"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_rejects_default_page_size_above_max() {
+        let file: FileConfig = toml::from_str(
+            r#"
+            [database]
+            url = "postgresql://localhost/test"
+
+            [pagination]
+            default_page_size = 50
+            max_page_size = 20
+            "#,
+        )
+        .unwrap();
+
+        let result = Config::resolve(file);
+
+        assert!(matches!(result, Err(Error::Internal(msg)) if msg.contains("MAX_PAGE_SIZE")));
+    }
+
+    #[test]
+    fn test_env_list_or_splits_and_trims() {
+        env::set_var(
+            "IRON_BBS_TEST_ORIGINS",
+            "https://a.example, https://b.example,,",
+        );
+
+        let origins = env_list_or("IRON_BBS_TEST_ORIGINS", None);
+
+        assert_eq!(origins, vec!["https://a.example", "https://b.example"]);
+        env::remove_var("IRON_BBS_TEST_ORIGINS");
+
+        let from_file = env_list_or(
+            "IRON_BBS_TEST_ORIGINS",
+            Some(vec!["https://c.example".to_string()]),
+        );
+        assert_eq!(from_file, vec!["https://c.example"]);
+        assert!(env_list_or("IRON_BBS_TEST_ORIGINS", None).is_empty());
+    }
+
+    #[test]
+    fn test_log_format_parses_accepted_values() {
+        assert_eq!("pretty".parse::<LogFormat>(), Ok(LogFormat::Pretty));
+        assert_eq!(" JSON ".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert!("logfmt".parse::<LogFormat>().is_err());
+        assert_eq!(LogFormat::default(), LogFormat::Pretty);
+    }
+
+    #[test]
+    fn test_env_or_falls_back_to_file_value() {
+        let result = env_or::<u16>("IRON_BBS_TEST_UNSET_PORT", Some(4000)).unwrap();
+
+        assert_eq!(result, Some(4000));
+    }
+
+    #[test]
+    fn test_guest_username_defaults_to_bbs_and_empty_disables() {
+        assert_eq!(guest_username(None), Some("bbs".to_string()));
+        assert_eq!(
+            guest_username(Some(" visitor ".to_string())),
+            Some("visitor".to_string())
+        );
+        assert_eq!(guest_username(Some("".to_string())), None);
+        assert_eq!(guest_username(Some("  ".to_string())), None);
+    }
+
+    fn valid_config() -> Config {
+        let file: FileConfig = toml::from_str(
+            r#"
+            [database]
+            url = "postgresql://localhost/test"
+
+            [web]
+            port = 3000
+
+            [ssh]
+            port = 2222
+            "#,
+        )
+        .unwrap();
+        let mut config = Config::resolve(file).unwrap();
+        config.ssh_host_key_path = std::env::temp_dir()
+            .join(format!("iron-bbs-{}", uuid::Uuid::new_v4()))
+            .join("ssh_host_key")
+            .display()
+            .to_string();
+        config
+    }
+
+    fn validation_error(config: &Config) -> String {
+        match config.validate() {
+            Err(Error::Internal(msg)) => msg,
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_defaults_and_missing_key_directories() {
+        valid_config().validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_non_postgres_urls() {
+        let mut config = valid_config();
+        config.database_url = "mysql://localhost/test".to_string();
+        assert!(validation_error(&config).contains("DATABASE_URL"));
+
+        config.database_url = "localhost:5432".to_string();
+        assert!(validation_error(&config).contains("DATABASE_URL"));
+
+        config.database_url = "postgres://localhost/test".to_string();
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_and_shared_ports() {
+        let mut config = valid_config();
+        config.ssh_port = 0;
+        assert!(validation_error(&config).contains("SSH_PORT must not be 0"));
+
+        config.ssh_port = config.web_port;
+        assert!(validation_error(&config).contains("must differ"));
+    }
+
+    #[test]
+    fn test_validate_rejects_host_key_under_a_file() {
+        let file = std::env::temp_dir().join(format!("iron-bbs-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&file, "not a directory").unwrap();
+
+        let mut config = valid_config();
+        config.ssh_host_key_path = file.join("ssh_host_key").display().to_string();
+        let msg = validation_error(&config);
+        std::fs::remove_file(&file).unwrap();
+
+        assert!(msg.contains("SSH_HOST_KEY_PATH"));
+    }
+}
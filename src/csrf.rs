@@ -1,13 +1,61 @@
 use axum::{
+    body::Body,
     extract::Request,
     http::{header, StatusCode},
     middleware::Next,
     response::Response,
 };
-use tower_cookies::Cookies;
+use serde::Deserialize;
+use tower_cookies::{cookie::SameSite, Cookie, Cookies};
+use uuid::Uuid;
 
-const CSRF_HEADER: &str = "x-csrf-token";
+use crate::error::ErrorMessage;
+
+pub(crate) const CSRF_HEADER: &str = "x-csrf-token";
 const CSRF_COOKIE: &str = "csrf_token";
+const CSRF_FORM_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct CsrfForm {
+    csrf_token: Option<String>,
+}
+
+/// Returns the current CSRF token, issuing a fresh cookie if the client has none.
+pub fn ensure_csrf_token(cookies: &Cookies) -> String {
+    if let Some(cookie) = cookies.get(CSRF_COOKIE) {
+        return cookie.value().to_string();
+    }
+
+    let token = Uuid::new_v4().simple().to_string();
+    let mut cookie = Cookie::new(CSRF_COOKIE, token.clone());
+    cookie.set_path("/");
+    cookie.set_same_site(SameSite::Strict);
+    cookies.add(cookie);
+
+    token
+}
+
+fn is_form_request(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .map(|ct| ct.starts_with("application/x-www-form-urlencoded"))
+        .unwrap_or(false)
+}
+
+fn forbidden() -> Response {
+    tracing::warn!("CSRF validation failed");
+    let mut response = Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .body("CSRF validation failed".into())
+        .unwrap();
+    // Browsers get the error page instead; a stale form is the usual cause.
+    response.extensions_mut().insert(ErrorMessage(
+        "This form has expired. Go back, reload the page and try again.".to_string(),
+    ));
+    response
+}
 
 pub async fn csrf_protection(cookies: Cookies, request: Request, next: Next) -> Response {
     let method = request.method().clone();
@@ -23,15 +71,26 @@ pub async fn csrf_protection(cookies: Cookies, request: Request, next: Next) ->
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string());
 
-    match (cookie_token, header_token) {
-        (Some(cookie), Some(header)) if cookie == header => next.run(request).await,
-        _ => {
-            tracing::warn!("CSRF validation failed");
-            Response::builder()
-                .status(StatusCode::FORBIDDEN)
-                .body("CSRF validation failed".into())
-                .unwrap()
+    // HTML forms can't set headers, so fall back to a hidden `csrf_token` field.
+    let (submitted_token, request) = match header_token {
+        Some(token) => (Some(token), request),
+        None if is_form_request(&request) => {
+            let (parts, body) = request.into_parts();
+            let bytes = match axum::body::to_bytes(body, CSRF_FORM_BODY_LIMIT).await {
+                Ok(bytes) => bytes,
+                Err(_) => return forbidden(),
+            };
+            let form_token = serde_urlencoded::from_bytes::<CsrfForm>(&bytes)
+                .ok()
+                .and_then(|form| form.csrf_token);
+            (form_token, Request::from_parts(parts, Body::from(bytes)))
         }
+        None => (None, request),
+    };
+
+    match (cookie_token, submitted_token) {
+        (Some(cookie), Some(submitted)) if cookie == submitted => next.run(request).await,
+        _ => forbidden(),
     }
 }
 
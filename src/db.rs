@@ -1,12 +1,811 @@
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use chrono::{DateTime, Utc};
+use sqlx::{
+    postgres::PgPoolOptions, Connection, Encode, PgConnection, PgPool, Postgres, QueryBuilder, Type,
+};
 use std::time::Duration;
+use tokio::sync::watch;
+use uuid::Uuid;
 
-pub async fn create_pool(database_url: &str) -> crate::Result<PgPool> {
-    let pool = PgPoolOptions::new()
-        .max_connections(20)
-        .acquire_timeout(Duration::from_secs(30))
-        .connect(database_url)
+use crate::models::{slugify, unique_slug, Board, OnlineUser, Post, PostCounts, PostRevision};
+
+/// How hard `create_pool` tries before giving up on an unreachable database.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectRetry {
+    /// Connection attempts in total; 1 disables retrying.
+    pub attempts: u32,
+    /// Cap on the wait between attempts, which starts at a second and doubles.
+    pub max_delay: Duration,
+}
+
+impl ConnectRetry {
+    /// Wait after failed attempt number `attempt` (counting from 1).
+    fn delay(&self, attempt: u32) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(16);
+        Duration::from_secs(1 << doublings).min(self.max_delay)
+    }
+}
+
+/// Whether a failed first connection may succeed later, as when Postgres is
+/// still starting. Bad URLs, TLS and authentication failures are not.
+fn is_transient_connect_error(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        // 57P03: cannot_connect_now, sent while the server starts up.
+        sqlx::Error::Database(e) => e.code().as_deref() == Some("57P03"),
+        _ => false,
+    }
+}
+
+/// Opens the pool, retrying with exponential backoff while the database is
+/// unreachable so the service survives starting before Postgres does.
+pub async fn create_pool(
+    database_url: &str,
+    max_connections: u32,
+    acquire_timeout: Duration,
+    retry: ConnectRetry,
+) -> crate::Result<PgPool> {
+    let options = PgPoolOptions::new()
+        .max_connections(max_connections)
+        .acquire_timeout(acquire_timeout);
+
+    let mut attempt = 1;
+    loop {
+        match options.clone().connect(database_url).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if attempt < retry.attempts && is_transient_connect_error(&e) => {
+                let delay = retry.delay(attempt);
+                tracing::warn!(
+                    "Database unavailable (attempt {}/{}): {}; retrying in {}s",
+                    attempt,
+                    retry.attempts,
+                    e,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+pub async fn delete_expired_sessions(db: &PgPool) -> crate::Result<u64> {
+    let result = sqlx::query!("DELETE FROM sessions WHERE expires_at < NOW()")
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// How long a client's `Idempotency-Key` keeps pointing at the post it created.
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+pub async fn delete_expired_password_reset_tokens(db: &PgPool) -> crate::Result<u64> {
+    let result = sqlx::query!("DELETE FROM password_reset_tokens WHERE expires_at < NOW()")
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn delete_expired_idempotency_keys(db: &PgPool) -> crate::Result<u64> {
+    let result = sqlx::query!(
+        "DELETE FROM idempotency_keys WHERE created_at < NOW() - make_interval(hours => $1)",
+        IDEMPOTENCY_KEY_TTL_HOURS as i32
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// The post `user_id` already created with idempotency `key`, if the key
+/// hasn't expired.
+pub async fn post_for_idempotency_key(
+    db: &PgPool,
+    user_id: Uuid,
+    key: &str,
+) -> crate::Result<Option<Post>> {
+    let post = sqlx::query_as!(
+        Post,
+        "SELECT p.* FROM idempotency_keys k
+         JOIN posts p ON p.id = k.post_id
+         WHERE k.user_id = $1 AND k.key = $2
+           AND k.created_at >= NOW() - make_interval(hours => $3)",
+        user_id,
+        key,
+        IDEMPOTENCY_KEY_TTL_HOURS as i32
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(post)
+}
+
+/// Creates a post and claims idempotency `key` for it in one transaction.
+///
+/// Returns `None`, creating nothing, when another request claimed the key
+/// first. The primary key on `(user_id, key)` makes a concurrent insert wait
+/// for the first transaction and then conflict, so only one post survives.
+pub async fn create_post_with_idempotency_key(
+    db: &PgPool,
+    user_id: Uuid,
+    key: &str,
+    title: &str,
+    content: &str,
+    published: bool,
+) -> crate::Result<Option<Post>> {
+    let mut tx = db.begin().await?;
+
+    // An expired claim no longer counts; let this request reuse the key.
+    sqlx::query!(
+        "DELETE FROM idempotency_keys
+         WHERE user_id = $1 AND key = $2 AND created_at < NOW() - make_interval(hours => $3)",
+        user_id,
+        key,
+        IDEMPOTENCY_KEY_TTL_HOURS as i32
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let mut post = sqlx::query_as!(
+        Post,
+        "INSERT INTO posts (title, content, author_id, published) VALUES ($1, $2, $3, $4) RETURNING *",
+        title,
+        content,
+        user_id,
+        published
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let claimed = sqlx::query!(
+        "INSERT INTO idempotency_keys (user_id, key, post_id) VALUES ($1, $2, $3)
+         ON CONFLICT (user_id, key) DO NOTHING",
+        user_id,
+        key,
+        post.id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        == 1;
+
+    if !claimed {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+
+    post.slug = Some(assign_post_slug(&mut tx, post.id, title).await?);
+    record_revision(&mut tx, &post, user_id).await?;
+    tx.commit().await?;
+    Ok(Some(post))
+}
+
+/// Periodically deletes expired sessions (and idempotency keys and password
+/// reset tokens) until `shutdown` flips to `true`.
+pub async fn run_session_cleanup(db: PgPool, every: Duration, mut shutdown: watch::Receiver<bool>) {
+    let mut interval = tokio::time::interval(every);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                match delete_expired_sessions(&db).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} expired sessions", removed),
+                    Err(e) => tracing::error!("Session cleanup failed: {}", e),
+                }
+                match delete_expired_idempotency_keys(&db).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} expired idempotency keys", removed),
+                    Err(e) => tracing::error!("Idempotency key cleanup failed: {}", e),
+                }
+                match delete_expired_password_reset_tokens(&db).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} expired password reset tokens", removed),
+                    Err(e) => tracing::error!("Password reset token cleanup failed: {}", e),
+                }
+            }
+            _ = shutdown.changed() => {
+                tracing::info!("Session cleanup task stopped");
+                return;
+            }
+        }
+    }
+}
+
+/// An `UPDATE ... SET` over optional columns that always bumps `updated_at`.
+///
+/// Each value is bound as its assignment is written, so placeholders and
+/// binds can't drift apart. Column names are `&'static str`: they can only
+/// come from the code, never from a request.
+struct PartialUpdate<'args> {
+    query: QueryBuilder<'args, Postgres>,
+}
+
+impl<'args> PartialUpdate<'args> {
+    fn new(table: &'static str) -> Self {
+        let mut query = QueryBuilder::new("UPDATE ");
+        query.push(table).push(" SET updated_at = NOW()");
+        Self { query }
+    }
+
+    /// Adds `column = <value>` when `value` is `Some`.
+    fn set<T>(&mut self, column: &'static str, value: Option<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.query
+                .push(", ")
+                .push(column)
+                .push(" = ")
+                .push_bind(value);
+        }
+        self
+    }
+
+    /// Targets the live (not soft-deleted) row `id` and returns it updated.
+    /// With `unchanged_since`, the row only matches while its `updated_at`
+    /// still equals that timestamp.
+    fn finish(
+        mut self,
+        id: Uuid,
+        unchanged_since: Option<DateTime<Utc>>,
+    ) -> QueryBuilder<'args, Postgres> {
+        self.query
+            .push(" WHERE id = ")
+            .push_bind(id)
+            .push(" AND deleted_at IS NULL");
+        if let Some(updated_at) = unchanged_since {
+            self.query.push(" AND updated_at = ").push_bind(updated_at);
+        }
+        self.query.push(" RETURNING *");
+        self.query
+    }
+}
+
+/// Applies a partial update to a post, touching only the fields that are `Some`.
+///
+/// When `expected_updated_at` is given the update only goes through if the
+/// post hasn't been edited since; otherwise it fails with `Error::Conflict`.
+/// Returns `Ok(None)` if the post doesn't exist.
+pub async fn update_post(
+    db: &PgPool,
+    id: Uuid,
+    title: Option<&str>,
+    content: Option<&str>,
+    published: Option<bool>,
+    expected_updated_at: Option<DateTime<Utc>>,
+    edited_by: Uuid,
+) -> crate::Result<Option<Post>> {
+    let mut update = PartialUpdate::new("posts");
+    update
+        .set("title", title)
+        .set("content", content)
+        .set("published", published);
+
+    let mut tx = db.begin().await?;
+    let post = update
+        .finish(id, expected_updated_at)
+        .build_query_as::<Post>()
+        .fetch_optional(&mut *tx)
+        .await?;
+    if let Some(post) = &post {
+        if title.is_some() || content.is_some() {
+            record_revision(&mut tx, post, edited_by).await?;
+        }
+    }
+    tx.commit().await?;
+
+    if post.is_none() && expected_updated_at.is_some() {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+            id
+        )
+        .fetch_one(db)
+        .await?;
+        if exists {
+            return Err(crate::Error::Conflict(
+                "This post was changed by someone else. Reload it and try again.".to_string(),
+            ));
+        }
+    }
+
+    Ok(post)
+}
+
+/// Tries at claiming a slug, should concurrent posts with the same title
+/// keep taking the one picked.
+const SLUG_ATTEMPTS: usize = 5;
+
+/// Gives post `post_id` a unique slug derived from `title` and returns it.
+///
+/// Each attempt runs in its own savepoint, so this can be part of a larger
+/// transaction and a lost race on the unique index just means trying again.
+pub async fn assign_post_slug(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    title: &str,
+) -> crate::Result<String> {
+    let base = slugify(title);
+
+    for _ in 0..SLUG_ATTEMPTS {
+        let taken = sqlx::query_scalar!(
+            r#"SELECT slug AS "slug!" FROM posts WHERE slug = $1 OR slug LIKE $1 || '-%'"#,
+            base
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        let slug = unique_slug(&base, &taken);
+
+        let mut savepoint = conn.begin().await?;
+        let claimed = sqlx::query!("UPDATE posts SET slug = $2 WHERE id = $1", post_id, slug)
+            .execute(&mut *savepoint)
+            .await;
+        match claimed {
+            Ok(_) => {
+                savepoint.commit().await?;
+                return Ok(slug);
+            }
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(crate::Error::Conflict(
+        "Could not find a free URL for this post, please try again".to_string(),
+    ))
+}
+
+/// Gives every top-level post that lacks a slug one, oldest first so older
+/// posts keep the unsuffixed names. Returns how many were filled in.
+pub async fn backfill_post_slugs(db: &PgPool) -> crate::Result<usize> {
+    let posts = sqlx::query!(
+        "SELECT id, title FROM posts WHERE slug IS NULL AND parent_id IS NULL ORDER BY created_at, id"
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut conn = db.acquire().await?;
+    for post in &posts {
+        assign_post_slug(&mut conn, post.id, &post.title).await?;
+    }
+
+    Ok(posts.len())
+}
+
+/// Makes the users named in `usernames` admins and everyone else not, so the
+/// operator's list is the only way to hold admin rights. Returns how many
+/// accounts changed.
+pub async fn sync_admins(conn: &mut PgConnection, usernames: &[String]) -> crate::Result<u64> {
+    let result = sqlx::query!(
+        "UPDATE users SET is_admin = (username = ANY($1))
+         WHERE is_admin IS DISTINCT FROM (username = ANY($1))",
+        usernames
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Replaces a post's tags with `tags`, creating any tag not seen before.
+///
+/// Runs on the caller's transaction, so a new post and its tags are saved
+/// together or not at all.
+pub async fn set_post_tags(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    tags: &[String],
+) -> crate::Result<()> {
+    sqlx::query!("DELETE FROM post_tags WHERE post_id = $1", post_id)
+        .execute(&mut *conn)
         .await?;
 
-    Ok(pool)
+    if !tags.is_empty() {
+        sqlx::query!(
+            "INSERT INTO tags (name) SELECT * FROM UNNEST($1::text[]) ON CONFLICT (name) DO NOTHING",
+            tags
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO post_tags (post_id, tag_id) SELECT $1, id FROM tags WHERE name = ANY($2)",
+            post_id,
+            tags
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Deletes an account by anonymizing it, all in one transaction.
+///
+/// The user row is kept rather than deleted: `posts.author_id` cascades, so a
+/// hard delete would take down the user's posts and, with them, every reply
+/// other people wrote underneath. Instead the row becomes a "[deleted]"
+/// placeholder author with no email, profile or usable password, its posts
+/// are soft-deleted, and everything that could sign the user back in
+/// (sessions, SSH keys, pending verifications, password reset links) or tie
+/// them to other content (votes) is removed. The auth log keeps its rows for
+/// auditing.
+pub async fn anonymize_user(
+    db: &PgPool,
+    user_id: Uuid,
+    unusable_password_hash: &str,
+) -> crate::Result<()> {
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "UPDATE posts SET deleted_at = NOW() WHERE author_id = $1 AND deleted_at IS NULL",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!("DELETE FROM post_votes WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!("DELETE FROM sessions WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!("DELETE FROM authorized_keys WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!(
+        "DELETE FROM email_verifications WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM password_reset_tokens WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let username = format!("[deleted]-{}", user_id.simple());
+    let email = format!("{}@deleted.invalid", user_id.simple());
+    let updated = sqlx::query!(
+        r#"
+        UPDATE users SET
+            username = $2, email = $3, password_hash = $4, display_name = '[deleted]',
+            bio = NULL, verified_at = NULL, is_admin = false,
+            last_login_ip = NULL, last_login_at = NULL, last_seen_at = NULL
+        WHERE id = $1
+        "#,
+        user_id,
+        username,
+        email,
+        unusable_password_hash
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Err(crate::Error::NotFound);
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// A post's tag names, alphabetically.
+pub async fn post_tags(db: &PgPool, post_id: Uuid) -> crate::Result<Vec<String>> {
+    let tags = sqlx::query_scalar!(
+        "SELECT t.name FROM tags t JOIN post_tags pt ON pt.tag_id = t.id WHERE pt.post_id = $1 ORDER BY t.name",
+        post_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(tags)
+}
+
+/// When `user_id` last loaded a post listing, or `None` before their first visit.
+pub async fn last_seen_at(db: &PgPool, user_id: Uuid) -> crate::Result<Option<DateTime<Utc>>> {
+    let seen = sqlx::query_scalar!("SELECT last_seen_at FROM users WHERE id = $1", user_id)
+        .fetch_optional(db)
+        .await?
+        .flatten();
+
+    Ok(seen)
+}
+
+/// Oldest revisions beyond this many are dropped as new ones are saved.
+pub const MAX_REVISIONS_PER_POST: i64 = 50;
+
+/// Saves `post`'s current title and content as its next revision, unless
+/// they match the latest one. Call it in the transaction that wrote the post.
+pub async fn record_revision(
+    conn: &mut PgConnection,
+    post: &Post,
+    edited_by: Uuid,
+) -> crate::Result<()> {
+    let latest = sqlx::query!(
+        "SELECT revision, title, content FROM post_revisions
+         WHERE post_id = $1 ORDER BY revision DESC LIMIT 1",
+        post.id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    if latest
+        .as_ref()
+        .is_some_and(|r| r.title == post.title && r.content == post.content)
+    {
+        return Ok(());
+    }
+    let revision = latest.map_or(1, |r| r.revision + 1);
+
+    sqlx::query!(
+        "INSERT INTO post_revisions (post_id, revision, title, content, edited_by)
+         VALUES ($1, $2, $3, $4, $5)",
+        post.id,
+        revision,
+        post.title,
+        post.content,
+        edited_by
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM post_revisions WHERE post_id = $1 AND revision <= $2",
+        post.id,
+        revision - MAX_REVISIONS_PER_POST as i32
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Every kept revision of `post_id`, newest first.
+pub async fn list_post_revisions(db: &PgPool, post_id: Uuid) -> crate::Result<Vec<PostRevision>> {
+    let revisions = sqlx::query_as!(
+        PostRevision,
+        "SELECT r.id, r.post_id, r.revision, r.title, r.content, r.edited_by,
+                u.username AS \"editor?\", r.created_at
+         FROM post_revisions r
+         LEFT JOIN users u ON u.id = r.edited_by
+         WHERE r.post_id = $1
+         ORDER BY r.revision DESC",
+        post_id
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(revisions)
+}
+
+/// Boards `viewer` may read and post to, by name. Private boards only show
+/// up for their members and admins.
+pub async fn visible_boards(db: &PgPool, viewer: Option<Uuid>) -> crate::Result<Vec<Board>> {
+    let boards = sqlx::query_as!(
+        Board,
+        "SELECT * FROM boards WHERE board_visible_to(id, $1) ORDER BY name ASC",
+        viewer
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(boards)
+}
+
+/// Whether `viewer` may read (and so post to) `board_id`; `None` is the
+/// unfiled board everyone can use.
+pub async fn can_use_board(
+    db: &PgPool,
+    board_id: Option<Uuid>,
+    viewer: Option<Uuid>,
+) -> crate::Result<bool> {
+    let visible = sqlx::query_scalar!(
+        r#"SELECT board_visible_to($1, $2) AS "visible!""#,
+        board_id,
+        viewer
+    )
+    .fetch_one(db)
+    .await?;
+    Ok(visible)
+}
+
+/// What `user_id` has written, split into posts, replies and drafts.
+pub async fn count_user_posts(db: &PgPool, user_id: Uuid) -> crate::Result<PostCounts> {
+    let counts = sqlx::query_as!(
+        PostCounts,
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE published AND parent_id IS NULL) AS "posts!",
+            COUNT(*) FILTER (WHERE published AND parent_id IS NOT NULL) AS "replies!",
+            COUNT(*) FILTER (WHERE NOT published) AS "drafts!"
+        FROM posts
+        WHERE author_id = $1 AND deleted_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(counts)
+}
+
+/// How many posts `viewer` can see were created after `since`: the unread
+/// count for a reader who last looked at `since`. With `None` (no visit yet)
+/// there is nothing to catch up on, so the count is 0 rather than every post
+/// on the board.
+pub async fn count_posts_since(
+    db: &PgPool,
+    viewer: Uuid,
+    since: Option<DateTime<Utc>>,
+) -> crate::Result<i64> {
+    let Some(since) = since else {
+        return Ok(0);
+    };
+
+    // A plain range on created_at, so idx_posts_not_deleted can serve it.
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM posts
+        WHERE created_at > $1
+            AND published = true AND parent_id IS NULL AND deleted_at IS NULL
+            AND board_visible_to(board_id, $2)
+        "#,
+        since,
+        viewer
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(count)
+}
+
+/// Users with a web session used since `since`, each with their earliest
+/// such session's sign-in time.
+pub async fn active_web_users(db: &PgPool, since: DateTime<Utc>) -> crate::Result<Vec<OnlineUser>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT u.username, MIN(s.created_at) AS "connected_at!"
+        FROM sessions s
+        JOIN users u ON s.user_id = u.id
+        WHERE s.last_active_at > $1 AND s.expires_at > NOW()
+        GROUP BY u.username
+        "#,
+        since
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| OnlineUser {
+            username: Some(row.username),
+            via: "web",
+            connected_at: row.connected_at,
+        })
+        .collect())
+}
+
+/// Records that `user_id` has now seen every post published so far.
+pub async fn mark_seen(db: &PgPool, user_id: Uuid) -> crate::Result<()> {
+    sqlx::query!(
+        "UPDATE users SET last_seen_at = NOW() WHERE id = $1",
+        user_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks `user_id` as welcomed. True only for the one call that did so, so
+/// the welcome is shown exactly once however many requests race for it.
+pub async fn take_welcome(db: &PgPool, user_id: Uuid) -> crate::Result<bool> {
+    let welcomed = sqlx::query_scalar!(
+        "UPDATE users SET welcomed_at = NOW() WHERE id = $1 AND welcomed_at IS NULL RETURNING id",
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(welcomed.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_retry_delay_doubles_up_to_the_cap() {
+        let retry = ConnectRetry {
+            attempts: 10,
+            max_delay: Duration::from_secs(5),
+        };
+        let delays: Vec<u64> = (1..=5).map(|n| retry.delay(n).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+        assert_eq!(retry.delay(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_only_unreachable_databases_are_retried() {
+        let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        assert!(is_transient_connect_error(&sqlx::Error::Io(refused)));
+        assert!(is_transient_connect_error(&sqlx::Error::PoolTimedOut));
+        assert!(!is_transient_connect_error(&sqlx::Error::Configuration(
+            "bad url".into()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_create_pool_fails_fast_on_a_malformed_url() {
+        let retry = ConnectRetry {
+            attempts: 5,
+            max_delay: Duration::from_secs(30),
+        };
+        let started = std::time::Instant::now();
+        let result = create_pool("not a database url", 1, Duration::from_secs(1), retry).await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    fn update_sql(title: Option<&str>, content: Option<&str>, published: Option<bool>) -> String {
+        let mut update = PartialUpdate::new("posts");
+        update
+            .set("title", title)
+            .set("content", content)
+            .set("published", published);
+        update.finish(Uuid::nil(), None).sql().to_string()
+    }
+
+    #[test]
+    fn test_partial_update_numbers_every_subset_in_order() {
+        let tail = "AND deleted_at IS NULL RETURNING *";
+        let cases = [
+            ((None, None, None), "".to_string(), 1),
+            ((Some("t"), None, None), ", title = $1".to_string(), 2),
+            ((None, Some("c"), None), ", content = $1".to_string(), 2),
+            ((None, None, Some(true)), ", published = $1".to_string(), 2),
+            (
+                (Some("t"), Some("c"), None),
+                ", title = $1, content = $2".to_string(),
+                3,
+            ),
+            (
+                (Some("t"), None, Some(true)),
+                ", title = $1, published = $2".to_string(),
+                3,
+            ),
+            (
+                (None, Some("c"), Some(false)),
+                ", content = $1, published = $2".to_string(),
+                3,
+            ),
+            (
+                (Some("t"), Some("c"), Some(true)),
+                ", title = $1, content = $2, published = $3".to_string(),
+                4,
+            ),
+        ];
+
+        for ((title, content, published), assignments, id_param) in cases {
+            assert_eq!(
+                update_sql(title, content, published),
+                format!(
+                    "UPDATE posts SET updated_at = NOW(){} WHERE id = ${} {}",
+                    assignments, id_param, tail
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_partial_update_guards_on_updated_at() {
+        let mut update = PartialUpdate::new("posts");
+        update.set("title", Some("t"));
+        assert_eq!(
+            update.finish(Uuid::nil(), Some(Utc::now())).sql(),
+            "UPDATE posts SET updated_at = NOW(), title = $1 WHERE id = $2 \
+             AND deleted_at IS NULL AND updated_at = $3 RETURNING *"
+        );
+    }
 }
@@ -1,10 +1,17 @@
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
+    Json,
 };
+use serde_json::json;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The user-facing message of an `Error` response, left in its extensions
+/// so the web layer can show it as a page instead of JSON.
+#[derive(Clone, Debug)]
+pub struct ErrorMessage(pub String);
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Database error: {0}")]
@@ -19,13 +26,86 @@ pub enum Error {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    /// Input that failed validation, as (field, message) pairs.
+    #[error("Validation failed: {0:?}")]
+    Validation(Vec<(String, String)>),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// The service is up but refusing this request for now, e.g. during
+    /// maintenance.
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Template error: {0}")]
+    Template(#[from] askama::Error),
+
     #[error("Internal server error")]
     Internal(String),
 }
 
+impl Error {
+    /// `Ok` when `fields` is empty, otherwise a `Validation` error for them.
+    pub fn validation(fields: Vec<(String, String)>) -> Result<()> {
+        if fields.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(fields))
+        }
+    }
+
+    /// Stable, machine-readable identifier for API clients.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Database(_) => "database_error",
+            Error::Io(_) => "io_error",
+            Error::NotFound => "not_found",
+            Error::Unauthorized => "unauthorized",
+            Error::Forbidden(_) => "forbidden",
+            Error::TooManyRequests(_) => "too_many_requests",
+            Error::BadRequest(_) => "bad_request",
+            Error::Validation(_) => "validation",
+            Error::Conflict(_) => "conflict",
+            Error::Unavailable(_) => "unavailable",
+            Error::Template(_) => "internal_error",
+            Error::Internal(_) => "internal_error",
+        }
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
+        if let Error::Validation(fields) = &self {
+            // The first message wins when a field failed more than one check.
+            let mut by_field = serde_json::Map::new();
+            for (field, message) in fields {
+                by_field
+                    .entry(field.clone())
+                    .or_insert_with(|| message.clone().into());
+            }
+            let body = json!({
+                "error": self.code(),
+                "message": "Validation failed",
+                "fields": by_field,
+            });
+            let mut response = (StatusCode::BAD_REQUEST, Json(body)).into_response();
+            response.extensions_mut().insert(ErrorMessage(
+                "Some of the values entered are not valid.".to_string(),
+            ));
+            return response;
+        }
+
+        let (status, message) = match &self {
             Error::Database(e) => {
                 tracing::error!("Database error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Database error")
@@ -36,12 +116,31 @@ impl IntoResponse for Error {
             }
             Error::NotFound => (StatusCode::NOT_FOUND, "Not found"),
             Error::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
+            Error::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.as_str()),
+            Error::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.as_str()),
+            Error::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
+            Error::Validation(_) => (StatusCode::BAD_REQUEST, "Validation failed"),
+            Error::Conflict(msg) => (StatusCode::CONFLICT, msg.as_str()),
+            Error::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.as_str()),
+            Error::Template(e) => {
+                tracing::error!("Template error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
             Error::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
             }
         };
 
-        (status, message).into_response()
+        let body = json!({
+            "error": self.code(),
+            "message": message,
+        });
+
+        let mut response = (status, Json(body)).into_response();
+        response
+            .extensions_mut()
+            .insert(ErrorMessage(message.to_string()));
+        response
     }
 }
@@ -0,0 +1,91 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+/// A strong validator for a body that is sent byte for byte as hashed.
+pub fn strong(body: &[u8]) -> HeaderValue {
+    tag("", body)
+}
+
+/// A weak validator for pages that can differ in small ways, such as a view
+/// counter, without being worth refetching.
+pub fn weak(data: &[u8]) -> HeaderValue {
+    tag("W/", data)
+}
+
+fn tag(prefix: &str, data: &[u8]) -> HeaderValue {
+    let digest = Sha256::digest(data);
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    // Hex digits and quotes are always valid in a header value.
+    HeaderValue::from_str(&format!("{}\"{}\"", prefix, hex))
+        .unwrap_or_else(|_| HeaderValue::from_static("\"\""))
+}
+
+/// Whether the request's `If-None-Match` already names `etag`. Comparison is
+/// weak, as RFC 9110 asks for this header, so `W/` prefixes are ignored.
+pub fn matches(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let Ok(ours) = etag.to_str() else {
+        return false;
+    };
+    let ours = ours.trim_start_matches("W/");
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|theirs| theirs == "*" || theirs.trim_start_matches("W/") == ours)
+}
+
+/// `304 Not Modified` if the client's copy is current, otherwise the response
+/// `build` makes. Either way the `ETag` header is set.
+pub fn respond(
+    headers: &HeaderMap,
+    etag: HeaderValue,
+    build: impl FnOnce() -> crate::Result<Response>,
+) -> crate::Result<Response> {
+    let mut response = if matches(headers, &etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        build()?
+    };
+    response.headers_mut().insert(header::ETAG, etag);
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn if_none_match(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_etags_follow_the_content() {
+        assert_eq!(strong(b"hello"), strong(b"hello"));
+        assert_ne!(strong(b"hello"), strong(b"hello!"));
+        assert!(strong(b"hello").to_str().unwrap().starts_with('"'));
+        assert!(weak(b"hello").to_str().unwrap().starts_with("W/\""));
+    }
+
+    #[test]
+    fn test_if_none_match_uses_weak_comparison() {
+        let etag = strong(b"hello");
+        let tag = etag.to_str().unwrap().to_string();
+
+        let mut headers = HeaderMap::new();
+        assert!(!matches(&headers, &etag));
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", W/{}", tag)).unwrap(),
+        );
+        assert!(matches(&headers, &etag));
+        assert!(matches(&if_none_match("*"), &etag));
+        assert!(!matches(&if_none_match("\"other\""), &etag));
+    }
+}
@@ -0,0 +1,155 @@
+use rss::extension::dublincore::DublinCoreExtensionBuilder;
+use rss::{ChannelBuilder, GuidBuilder, ItemBuilder};
+
+use crate::{
+    models::{Board, PostWithAuthor},
+    Error, Result,
+};
+
+/// Number of posts included in the feed.
+pub const FEED_LIMIT: i64 = 20;
+
+const PREVIEW_LENGTH: usize = 300;
+
+/// Serializes `posts` as an RSS 2.0 document, linking items under `base_url`.
+pub fn render_rss(posts: &[PostWithAuthor], base_url: &str) -> Result<String> {
+    render_channel("Iron BBS", "/", "Latest posts on Iron BBS", posts, base_url)
+}
+
+/// Like `render_rss`, for the posts of one board. The channel is named and
+/// described after the board and links to its page.
+pub fn render_board_rss(board: &Board, posts: &[PostWithAuthor], base_url: &str) -> Result<String> {
+    let description = board
+        .description
+        .clone()
+        .filter(|description| !description.trim().is_empty())
+        .unwrap_or_else(|| format!("Latest posts in {} on Iron BBS", board.name));
+    render_channel(
+        &format!("{} - Iron BBS", board.name),
+        &format!("/boards/{}", board.slug),
+        &description,
+        posts,
+        base_url,
+    )
+}
+
+/// All text goes through the XML writer, so `&` and `<` in titles and bodies
+/// are escaped rather than breaking the document.
+fn render_channel(
+    title: &str,
+    path: &str,
+    description: &str,
+    posts: &[PostWithAuthor],
+    base_url: &str,
+) -> Result<String> {
+    let base_url = base_url.trim_end_matches('/');
+
+    let items = posts
+        .iter()
+        .map(|post| {
+            let link = format!("{}/posts/{}", base_url, post.id);
+            ItemBuilder::default()
+                .title(post.title.clone())
+                .link(link.clone())
+                .description(post.preview(PREVIEW_LENGTH))
+                .guid(GuidBuilder::default().value(link).permalink(true).build())
+                .pub_date(post.created_at.to_rfc2822())
+                .dublin_core_ext(
+                    DublinCoreExtensionBuilder::default()
+                        .creator(post.author_name().to_string())
+                        .build(),
+                )
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let channel = ChannelBuilder::default()
+        .title(title)
+        .link(format!("{}{}", base_url, path))
+        .description(description)
+        .last_build_date(posts.first().map(|p| p.created_at.to_rfc2822()))
+        .items(items)
+        .build();
+
+    let xml = channel
+        .write_to(Vec::new())
+        .map_err(|e| Error::Internal(format!("Failed to write feed: {}", e)))?;
+    String::from_utf8(xml).map_err(|e| Error::Internal(format!("Feed is not UTF-8: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn post(title: &str, content: &str) -> PostWithAuthor {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+        PostWithAuthor {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            content: content.to_string(),
+            author_id: Uuid::new_v4(),
+            author_username: "alice".to_string(),
+            author_display_name: None,
+            author_email: "alice@example.com".to_string(),
+            board_id: None,
+            board_name: None,
+            board_slug: None,
+            parent_id: None,
+            created_at,
+            updated_at: created_at,
+            published: true,
+            pinned: false,
+            vote_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_escapes_markup_in_titles_and_descriptions() {
+        let posts = vec![post("Fish & <Chips>", "1 < 2 && 3 > 2")];
+
+        let xml = render_rss(&posts, "http://localhost:3000/").unwrap();
+        let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+
+        assert!(xml.contains("Fish &amp; &lt;Chips&gt;"));
+        let item = &channel.items()[0];
+        assert_eq!(item.title(), Some("Fish & <Chips>"));
+        assert_eq!(item.description(), Some("1 < 2 && 3 > 2"));
+        assert_eq!(
+            item.link(),
+            Some(format!("http://localhost:3000/posts/{}", posts[0].id).as_str())
+        );
+        assert_eq!(item.pub_date(), Some("Fri, 1 Mar 2024 12:30:00 +0000"));
+        assert!(!xml.contains("alice@example.com"));
+    }
+
+    #[test]
+    fn test_board_feed_describes_the_board() {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+        let mut board = Board {
+            id: Uuid::new_v4(),
+            name: "Rust & Friends".to_string(),
+            slug: "rust".to_string(),
+            description: Some("Borrowing, lending".to_string()),
+            created_at,
+            updated_at: created_at,
+            visibility: "public".to_string(),
+        };
+
+        let xml = render_board_rss(&board, &[], "http://localhost:3000").unwrap();
+        let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+        assert_eq!(channel.title(), "Rust & Friends - Iron BBS");
+        assert_eq!(channel.link(), "http://localhost:3000/boards/rust");
+        assert_eq!(channel.description(), "Borrowing, lending");
+        assert!(channel.items().is_empty());
+
+        board.description = None;
+        let xml = render_board_rss(&board, &[], "http://localhost:3000").unwrap();
+        let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+        assert_eq!(
+            channel.description(),
+            "Latest posts in Rust & Friends on Iron BBS"
+        );
+    }
+}
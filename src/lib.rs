@@ -1,9 +1,19 @@
 pub mod auth;
+pub mod avatar;
 pub mod config;
 pub mod csrf;
 pub mod db;
 pub mod error;
+pub mod etag;
+pub mod feed;
+pub mod maintenance;
+pub mod markdown;
 pub mod models;
+pub mod pow;
+pub mod presence;
+pub mod ratelimit;
+pub mod request_id;
+pub mod security_headers;
 pub mod ssh;
 pub mod web;
 
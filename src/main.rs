@@ -1,6 +1,12 @@
-use iron_bbs::{Config, Result};
+use iron_bbs::{config::LogFormat, Config, Result};
+use std::time::Duration;
 use tokio::signal;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
+
+/// How long the servers get to wind down before the process exits anyway.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
 
 async fn shutdown_signal() {
     let ctrl_c = async {
@@ -32,45 +38,150 @@ async fn shutdown_signal() {
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let fmt_layer = match LogFormat::from_env()? {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().boxed(),
+    };
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "iron_bbs=debug,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(fmt_layer)
         .init();
 
-    let config = Config::from_env()?;
+    let config = Config::load()?;
+    config.validate()?;
 
     tracing::info!("Starting iron-bbs");
     tracing::info!("Web server will listen on: {}", config.web_addr());
     tracing::info!("SSH server will listen on: {}", config.ssh_addr());
 
-    let db_pool = iron_bbs::db::create_pool(&config.database_url).await?;
+    let db_pool = iron_bbs::db::create_pool(
+        &config.database_url,
+        config.db_max_connections,
+        Duration::from_secs(config.db_acquire_timeout_secs),
+        iron_bbs::db::ConnectRetry {
+            attempts: config.db_connect_attempts,
+            max_delay: Duration::from_secs(config.db_connect_max_delay_secs),
+        },
+    )
+    .await?;
 
     sqlx::migrate!("./migrations")
         .run(&db_pool)
         .await
         .expect("Failed to run migrations");
 
-    let app_state = iron_bbs::web::AppState::new(db_pool.clone());
+    let changed =
+        iron_bbs::db::sync_admins(&mut *db_pool.acquire().await?, &config.admin_usernames).await?;
+    if changed > 0 {
+        tracing::info!("Updated admin rights for {} accounts", changed);
+    }
+    if config.admin_usernames.is_empty() {
+        tracing::warn!("ADMIN_USERNAMES is empty, so nobody can use the admin routes");
+    }
+
+    let slugged = iron_bbs::db::backfill_post_slugs(&db_pool).await?;
+    if slugged > 0 {
+        tracing::info!("Gave {} existing posts a URL slug", slugged);
+    }
+
+    let (cleanup_shutdown_tx, cleanup_shutdown_rx) = watch::channel(false);
+    let cleanup_handle = tokio::spawn(iron_bbs::db::run_session_cleanup(
+        db_pool.clone(),
+        Duration::from_secs(config.session_cleanup_secs),
+        cleanup_shutdown_rx,
+    ));
+
+    let app_state = iron_bbs::web::AppState::with_settings(
+        db_pool.clone(),
+        iron_bbs::web::WebSettings {
+            registration_pow_bits: config.registration_pow_bits,
+            sessions: iron_bbs::web::SessionLifetimes {
+                idle_timeout: chrono::Duration::seconds(config.session_idle_timeout_secs as i64),
+                max_age: chrono::Duration::seconds(config.session_max_age_secs as i64),
+            },
+            posts_per_window: config.post_rate_limit,
+            post_window: Duration::from_secs(config.post_rate_window_secs),
+            avatar_provider: config.avatar_provider,
+            password_params: config.password_params(),
+            trust_proxy: config.trust_proxy,
+            default_page_size: config.default_page_size as i64,
+            max_page_size: config.max_page_size as i64,
+            maintenance_mode: config.maintenance_mode,
+            welcome_message: config.welcome_message.clone(),
+        },
+    );
 
-    let web_handle = tokio::spawn(iron_bbs::web::serve(config.web_addr(), app_state));
-    let ssh_handle = tokio::spawn(iron_bbs::ssh::serve(config.ssh_addr(), db_pool.clone()));
+    let shutdown = CancellationToken::new();
+
+    let mut web_handle = tokio::spawn({
+        let addr = config.web_addr();
+        let app_state = app_state.clone();
+        let allowed_origins = config.allowed_origins.clone();
+        let csp = config.content_security_policy.clone();
+        let shutdown = shutdown.clone().cancelled_owned();
+        async move {
+            iron_bbs::web::serve(addr, app_state, &allowed_origins, csp.as_deref(), shutdown).await
+        }
+    });
+    let ssh_options = iron_bbs::ssh::SshOptions {
+        addr: config.ssh_addr(),
+        host_key_path: config.ssh_host_key_path.clone(),
+        inactivity_timeout: Duration::from_secs(config.ssh_inactivity_timeout_secs),
+        max_sessions: config.ssh_max_sessions,
+        motd_path: config.ssh_motd_path.clone(),
+        guest_username: config.guest_username.clone(),
+        page_size: config.default_page_size,
+        password_params: config.password_params(),
+        maintenance: app_state.maintenance.clone(),
+        idle_logout: (config.ssh_idle_logout_secs > 0)
+            .then(|| Duration::from_secs(config.ssh_idle_logout_secs)),
+        post_limiter: app_state.post_limiter.clone(),
+    };
+    let mut ssh_handle = tokio::spawn(iron_bbs::ssh::serve(
+        ssh_options,
+        db_pool.clone(),
+        app_state.login_limiter.clone(),
+        app_state.post_events.clone(),
+        app_state.presence.clone(),
+        shutdown.clone(),
+    ));
 
     tokio::select! {
-        result = web_handle => {
+        result = &mut web_handle => {
             tracing::error!("Web server exited: {:?}", result);
         }
-        result = ssh_handle => {
+        result = &mut ssh_handle => {
             tracing::error!("SSH server exited: {:?}", result);
         }
         _ = shutdown_signal() => {
-            tracing::info!("Shutdown signal received, closing database connections...");
-            db_pool.close().await;
-            tracing::info!("Graceful shutdown complete");
+            tracing::info!("Shutdown signal received, stopping web and SSH servers...");
         }
     }
 
+    shutdown.cancel();
+    let servers = async {
+        if !web_handle.is_finished() {
+            web_handle.await.ok();
+        }
+        if !ssh_handle.is_finished() {
+            ssh_handle.await.ok();
+        }
+    };
+    if tokio::time::timeout(SHUTDOWN_GRACE, servers).await.is_err() {
+        tracing::warn!(
+            "Servers did not stop within {:?}, exiting anyway",
+            SHUTDOWN_GRACE
+        );
+    }
+
+    tracing::info!("Closing database connections...");
+    cleanup_shutdown_tx.send(true).ok();
+    cleanup_handle.await.ok();
+    db_pool.close().await;
+    tracing::info!("Graceful shutdown complete");
+
     Ok(())
 }
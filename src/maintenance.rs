@@ -0,0 +1,56 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{Error, Result};
+
+/// Shown to anyone who tries to change something while the board is read-only.
+pub const READ_ONLY_MESSAGE: &str =
+    "The board is read-only while maintenance is in progress. Please try again later.";
+
+/// Read-only switch shared by the web and SSH front ends. Browsing keeps
+/// working while it is on; posting, editing and other changes are refused.
+#[derive(Debug, Default)]
+pub struct Maintenance {
+    enabled: AtomicBool,
+}
+
+impl Maintenance {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// `Err(Error::Unavailable)` while maintenance mode is on.
+    pub fn check(&self) -> Result<()> {
+        if self.is_enabled() {
+            Err(Error::Unavailable(READ_ONLY_MESSAGE.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_refuses_writes_only_while_enabled() {
+        let maintenance = Maintenance::new(false);
+        assert!(maintenance.check().is_ok());
+
+        maintenance.set(true);
+        assert!(matches!(maintenance.check(), Err(Error::Unavailable(_))));
+
+        maintenance.set(false);
+        assert!(maintenance.check().is_ok());
+    }
+}
@@ -0,0 +1,56 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Renders user-supplied Markdown to HTML that is safe to embed unescaped.
+///
+/// The raw output of the Markdown renderer passes inline HTML straight through,
+/// so it is run through `ammonia` to drop scripts, event handlers and
+/// `javascript:` URLs.
+pub fn render(content: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let parser = Parser::new_ext(content, options);
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+
+    ammonia::Builder::default()
+        // Task list items render as disabled checkboxes.
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .clean(&html_output)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_markdown() {
+        let html = render("# Title\n\nSome **bold** text and a [link](https://example.com).");
+
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("href=\"https://example.com\""));
+    }
+
+    #[test]
+    fn test_strips_script_tags() {
+        let html = render("Hello <script>alert('xss')</script> world");
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert("));
+        assert!(html.contains("Hello"));
+    }
+
+    #[test]
+    fn test_strips_event_handlers_and_javascript_urls() {
+        let html =
+            render("<img src=\"x.png\" onerror=\"alert(1)\">\n\n[click](javascript:alert(1))");
+
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+    }
+}
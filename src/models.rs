@@ -1,7 +1,277 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::avatar::{self, AvatarProvider};
+
+/// Saves within this long of posting don't count as an edit.
+const EDIT_GRACE_SECS: i64 = 60;
+
+/// Whether a post was changed meaningfully after it was first saved.
+fn edited_after(created_at: DateTime<Utc>, updated_at: DateTime<Utc>) -> bool {
+    updated_at - created_at > Duration::seconds(EDIT_GRACE_SECS)
+}
+
+/// Whether a post created at `created_at` is new to a reader who last
+/// looked at the list at `last_seen_at`. Before the first visit, everything is.
+pub fn is_unread(created_at: DateTime<Utc>, last_seen_at: Option<DateTime<Utc>>) -> bool {
+    last_seen_at.map_or(true, |seen| created_at > seen)
+}
+
+/// Describes `then` relative to `now`, e.g. "5 minutes ago". Anything older
+/// than a month is shown as a date instead.
+pub fn relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - then;
+    let plural =
+        |n: i64, unit: &str| format!("{} {}{} ago", n, unit, if n == 1 { "" } else { "s" });
+
+    if elapsed < Duration::minutes(1) {
+        "just now".to_string()
+    } else if elapsed < Duration::hours(1) {
+        plural(elapsed.num_minutes(), "minute")
+    } else if elapsed < Duration::days(1) {
+        plural(elapsed.num_hours(), "hour")
+    } else if elapsed < Duration::days(30) {
+        plural(elapsed.num_days(), "day")
+    } else {
+        format!("on {}", then.format("%B %d, %Y"))
+    }
+}
+
+/// Longest post title accepted from the web or SSH, in characters.
+pub const MAX_POST_TITLE_CHARS: usize = 200;
+/// Longest post body accepted from the web or SSH, in characters.
+pub const MAX_POST_CONTENT_CHARS: usize = 50_000;
+
+/// Longest display name, matching the `VARCHAR(50)` column.
+pub const MAX_DISPLAY_NAME_CHARS: usize = 50;
+/// Longest profile bio, in characters.
+pub const MAX_BIO_CHARS: usize = 500;
+
+/// Trims `raw`, drops control characters (keeping newlines when
+/// `multiline`), and maps an empty result to `None`.
+fn clean_profile_text(raw: &str, multiline: bool) -> Option<String> {
+    let cleaned: String = raw
+        .replace("\r\n", "\n")
+        .chars()
+        .filter(|c| !c.is_control() || (multiline && *c == '\n'))
+        .collect();
+    let cleaned = cleaned.trim();
+    (!cleaned.is_empty()).then(|| cleaned.to_string())
+}
+
+/// Cleans a submitted display name; blank means "use my username".
+pub fn normalize_display_name(raw: &str) -> Result<Option<String>, String> {
+    let name = clean_profile_text(raw, false);
+    if name
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_DISPLAY_NAME_CHARS)
+    {
+        return Err(format!(
+            "Display name must be at most {} characters",
+            MAX_DISPLAY_NAME_CHARS
+        ));
+    }
+    Ok(name)
+}
+
+/// Cleans a submitted bio, keeping line breaks.
+pub fn normalize_bio(raw: &str) -> Result<Option<String>, String> {
+    let bio = clean_profile_text(raw, true);
+    if bio
+        .as_ref()
+        .is_some_and(|b| b.chars().count() > MAX_BIO_CHARS)
+    {
+        return Err(format!("Bio must be at most {} characters", MAX_BIO_CHARS));
+    }
+    Ok(bio)
+}
+
+/// Longest board name or slug, matching the `VARCHAR(100)` columns.
+pub const MAX_BOARD_FIELD_CHARS: usize = 100;
+
+/// Checks a board name is present and fits its column.
+pub fn validate_board_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Board name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_BOARD_FIELD_CHARS {
+        return Err(format!(
+            "Board name must be at most {} characters",
+            MAX_BOARD_FIELD_CHARS
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a board slug is URL-safe: lowercase ASCII words joined by single hyphens.
+pub fn validate_board_slug(slug: &str) -> Result<(), String> {
+    let well_formed = !slug.is_empty()
+        && slug.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        });
+    if !well_formed {
+        return Err(
+            "Slug must be lowercase letters and digits separated by single hyphens".to_string(),
+        );
+    }
+    if slug.len() > MAX_BOARD_FIELD_CHARS {
+        return Err(format!(
+            "Slug must be at most {} characters",
+            MAX_BOARD_FIELD_CHARS
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a post title against `MAX_POST_TITLE_CHARS` and `validate_post_text`,
+/// returning a user-facing reason.
+pub fn validate_post_title(title: &str) -> Result<(), String> {
+    if title.chars().count() > MAX_POST_TITLE_CHARS {
+        return Err(format!(
+            "Title must be at most {} characters",
+            MAX_POST_TITLE_CHARS
+        ));
+    }
+    validate_post_text(title)
+}
+
+/// Checks a post body against `MAX_POST_CONTENT_CHARS` and `validate_post_text`,
+/// returning a user-facing reason.
+pub fn validate_post_content(content: &str) -> Result<(), String> {
+    if content.chars().count() > MAX_POST_CONTENT_CHARS {
+        return Err(format!(
+            "Content must be at most {} characters",
+            MAX_POST_CONTENT_CHARS
+        ));
+    }
+    validate_post_text(content)
+}
+
+/// Rejects NUL and other control characters, keeping tabs and line breaks.
+/// Postgres refuses NUL in `text` columns with an opaque error, and the SSH
+/// editor would otherwise store whatever bytes a client sends.
+pub fn validate_post_text(text: &str) -> Result<(), String> {
+    if text.contains('\0') {
+        return Err("Text must not contain NUL characters".to_string());
+    }
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+    {
+        return Err("Text must not contain control characters".to_string());
+    }
+    Ok(())
+}
+
+/// Most tags a single post may carry.
+pub const MAX_TAGS_PER_POST: usize = 5;
+/// Longest tag, matching the `VARCHAR(30)` column.
+pub const MAX_TAG_CHARS: usize = 30;
+
+/// Parses a comma-separated tag list into lowercase, trimmed, deduplicated
+/// tags in the order given. Spaces inside a tag become hyphens and a leading
+/// `#` is dropped, so "Rust, #async io" yields `["rust", "async-io"]`.
+pub fn parse_tags(input: &str) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for raw in input.split(',') {
+        let raw = raw.trim();
+        let raw = raw.strip_prefix('#').unwrap_or(raw);
+        let tag = raw
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        if tag.is_empty() || tags.contains(&tag) {
+            continue;
+        }
+
+        if !tag
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(format!(
+                "Tag \"{}\" may only contain letters, digits and hyphens",
+                tag
+            ));
+        }
+        if tag.chars().count() > MAX_TAG_CHARS {
+            return Err(format!("Tags must be at most {} characters", MAX_TAG_CHARS));
+        }
+        tags.push(tag);
+    }
+
+    if tags.len() > MAX_TAGS_PER_POST {
+        return Err(format!(
+            "A post can have at most {} tags",
+            MAX_TAGS_PER_POST
+        ));
+    }
+    Ok(tags)
+}
+
+/// Longest slug `slugify` produces, before any `-N` suffix.
+pub const MAX_SLUG_CHARS: usize = 80;
+
+/// URL slug for a post title: lowercase, with every run of characters other
+/// than letters and digits (in any script) collapsed into one hyphen, so
+/// "Hello, World!" becomes `hello-world` and "Café über" `café-über`. A title
+/// with nothing usable left, such as "!!!", gets `post`.
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug: String = slug
+        .trim_end_matches('-')
+        .chars()
+        .take(MAX_SLUG_CHARS)
+        .collect();
+    match slug.trim_end_matches('-') {
+        "" => "post".to_string(),
+        slug => slug.to_string(),
+    }
+}
+
+/// `base` if it is free, otherwise `base-2`, `base-3`, ... whichever comes
+/// first that is not in `taken`.
+pub fn unique_slug(base: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|slug| slug == base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default()
+}
+
+/// Longest abuse-report reason, matching the `VARCHAR(500)` column.
+pub const MAX_REPORT_REASON_CHARS: usize = 500;
+
+/// Trims an abuse-report reason and checks it is present and not too long.
+pub fn parse_report_reason(input: &str) -> Result<String, String> {
+    let reason = input.trim();
+    if reason.is_empty() {
+        return Err("Please say what is wrong with this post".to_string());
+    }
+    if reason.chars().count() > MAX_REPORT_REASON_CHARS {
+        return Err(format!(
+            "Reasons must be at most {} characters",
+            MAX_REPORT_REASON_CHARS
+        ));
+    }
+    Ok(reason.to_string())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: Uuid,
@@ -11,21 +281,31 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub last_login_ip: Option<String>,
     pub last_login_at: Option<DateTime<Utc>>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub is_admin: bool,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
 }
 
 impl User {
-    pub fn gravatar_url(&self, size: u32) -> String {
-        let email_hash = format!(
-            "{:x}",
-            md5::compute(self.email.trim().to_lowercase().as_bytes())
-        );
-        format!(
-            "https://www.gravatar.com/avatar/{}?s={}&d=identicon",
-            email_hash, size
-        )
+    /// Name to show other people: the display name if set, else the username.
+    pub fn shown_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.username)
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+
+    /// Takes the provider by reference, as templates pass their fields.
+    pub fn avatar_url(&self, provider: &AvatarProvider, size: u32) -> String {
+        avatar::avatar_url(*provider, &self.email, &self.username, size)
     }
 }
 
+/// One row of `posts`, column for column. Queries that load whole rows use
+/// `query_as!(Post, "SELECT * ...")`, so a column added by a migration
+/// without a matching field here fails the build instead of at runtime.
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Post {
     pub id: Uuid,
@@ -33,12 +313,21 @@ pub struct Post {
     pub content: String,
     pub author_id: Uuid,
     pub board_id: Option<Uuid>,
+    pub parent_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub published: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub view_count: i64,
+    pub pinned: bool,
+    /// Name in `/p/:slug` URLs; replies have none.
+    pub slug: Option<String>,
 }
 
 impl Post {
+    pub fn was_edited(&self) -> bool {
+        edited_after(self.created_at, self.updated_at)
+    }
     pub fn preview(&self, length: usize) -> String {
         let chars: String = self.content.chars().take(length).collect();
         if self.content.chars().count() > length {
@@ -47,18 +336,6 @@ impl Post {
             chars
         }
     }
-
-    pub fn content_html(&self) -> String {
-        use pulldown_cmark::{html, Options, Parser};
-        let mut options = Options::empty();
-        options.insert(Options::ENABLE_STRIKETHROUGH);
-        options.insert(Options::ENABLE_TABLES);
-        options.insert(Options::ENABLE_TASKLISTS);
-        let parser = Parser::new_ext(&self.content, options);
-        let mut html_output = String::new();
-        html::push_html(&mut html_output, parser);
-        html_output
-    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,16 +345,35 @@ pub struct PostWithAuthor {
     pub content: String,
     pub author_id: Uuid,
     pub author_username: String,
+    pub author_display_name: Option<String>,
     pub author_email: String,
     pub board_id: Option<Uuid>,
     pub board_name: Option<String>,
     pub board_slug: Option<String>,
+    pub parent_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub published: bool,
+    pub pinned: bool,
+    pub vote_count: i64,
 }
 
 impl PostWithAuthor {
+    pub fn was_edited(&self) -> bool {
+        edited_after(self.created_at, self.updated_at)
+    }
+
+    /// When the post was last edited, relative to now.
+    pub fn edited_relative(&self) -> String {
+        relative_time(self.updated_at, Utc::now())
+    }
+    /// The author's display name if set, else their username.
+    pub fn author_name(&self) -> &str {
+        self.author_display_name
+            .as_deref()
+            .unwrap_or(&self.author_username)
+    }
+
     pub fn preview(&self, length: usize) -> String {
         let chars: String = self.content.chars().take(length).collect();
         if self.content.chars().count() > length {
@@ -87,27 +383,8 @@ impl PostWithAuthor {
         }
     }
 
-    pub fn author_gravatar(&self, size: u32) -> String {
-        let email_hash = format!(
-            "{:x}",
-            md5::compute(self.author_email.trim().to_lowercase().as_bytes())
-        );
-        format!(
-            "https://www.gravatar.com/avatar/{}?s={}&d=identicon",
-            email_hash, size
-        )
-    }
-
-    pub fn content_html(&self) -> String {
-        use pulldown_cmark::{html, Options, Parser};
-        let mut options = Options::empty();
-        options.insert(Options::ENABLE_STRIKETHROUGH);
-        options.insert(Options::ENABLE_TABLES);
-        options.insert(Options::ENABLE_TASKLISTS);
-        let parser = Parser::new_ext(&self.content, options);
-        let mut html_output = String::new();
-        html::push_html(&mut html_output, parser);
-        html_output
+    pub fn author_avatar(&self, provider: &AvatarProvider, size: u32) -> String {
+        avatar::avatar_url(*provider, &self.author_email, &self.author_username, size)
     }
 }
 
@@ -119,6 +396,31 @@ pub struct Board {
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// `public`, or `private` for boards only members (and admins) can see.
+    pub visibility: String,
+}
+
+impl Board {
+    pub fn is_private(&self) -> bool {
+        self.visibility == BoardVisibility::Private.as_str()
+    }
+}
+
+/// Who can read and post to a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardVisibility {
+    Public,
+    Private,
+}
+
+impl BoardVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoardVisibility::Public => "public",
+            BoardVisibility::Private => "private",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -130,6 +432,29 @@ pub struct Session {
     pub expires_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuthEvent {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub username: String,
+    pub event_type: String,
+    pub ip: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An unresolved abuse report, joined with the reported post and reporter.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ReportWithPost {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub post_title: String,
+    pub reporter_username: Option<String>,
+    pub reporter_ip: String,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AuthorizedKey {
     pub id: Uuid,
@@ -139,3 +464,428 @@ pub struct AuthorizedKey {
     pub comment: Option<String>,
     pub created_at: DateTime<Utc>,
 }
+
+/// Everything stored about one account, as downloaded from `/account/export`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountExport {
+    pub exported_at: DateTime<Utc>,
+    pub profile: ExportedProfile,
+    /// Every post and reply the user wrote, including drafts and deleted ones.
+    pub posts: Vec<Post>,
+    pub authorized_keys: Vec<AuthorizedKey>,
+    pub sessions: Vec<SessionInfo>,
+}
+
+/// A `User` without its password hash.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedProfile {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub last_login_ip: Option<String>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub is_admin: bool,
+}
+
+impl From<User> for ExportedProfile {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            display_name: user.display_name,
+            bio: user.bio,
+            created_at: user.created_at,
+            verified_at: user.verified_at,
+            last_login_ip: user.last_login_ip,
+            last_login_at: user.last_login_at,
+            is_admin: user.is_admin,
+        }
+    }
+}
+
+/// Session metadata shown to its owner; the token itself is never exposed.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub last_active_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session the export was requested from.
+    pub current: bool,
+}
+
+/// How much a user has written, for their account overview. Deleted posts
+/// are left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, sqlx::FromRow)]
+pub struct PostCounts {
+    /// Published top-level posts.
+    pub posts: i64,
+    pub replies: i64,
+    pub drafts: i64,
+}
+
+/// Someone currently connected, over SSH or a recently active web session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OnlineUser {
+    /// `None` for SSH guests.
+    pub username: Option<String>,
+    pub via: &'static str,
+    pub connected_at: DateTime<Utc>,
+}
+
+impl OnlineUser {
+    pub fn shown_name(&self) -> &str {
+        self.username.as_deref().unwrap_or("guest")
+    }
+
+    /// Time since `connected_at`, to the minute: "4m", "2h 05m", "3d 1h".
+    pub fn connected_for(&self, now: DateTime<Utc>) -> String {
+        let minutes = (now - self.connected_at).num_minutes().max(0);
+        let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+        if days > 0 {
+            format!("{}d {}h", days, hours)
+        } else if hours > 0 {
+            format!("{}h {:02}m", hours, minutes)
+        } else {
+            format!("{}m", minutes)
+        }
+    }
+}
+
+/// One saved version of a post. Revision 1 is the post as first written.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PostRevision {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub revision: i32,
+    pub title: String,
+    pub content: String,
+    pub edited_by: Option<Uuid>,
+    /// Username of `edited_by`, if the account still exists.
+    pub editor: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PostRevision {
+    pub fn editor_name(&self) -> &str {
+        self.editor.as_deref().unwrap_or("[deleted]")
+    }
+}
+
+/// Whether a line of a [`diff_lines`] result was kept, added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+impl DiffLine {
+    pub fn marker(&self) -> &'static str {
+        match self.kind {
+            DiffKind::Same => " ",
+            DiffKind::Added => "+",
+            DiffKind::Removed => "-",
+        }
+    }
+}
+
+/// Above this many cells the longest-common-subsequence table gets too big,
+/// and [`diff_lines`] shows the changed middle as removed-then-added instead.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Line-by-line diff from `old` to `new`, removals before additions.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+
+    // Lines shared at either end need no table.
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut out: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|l| line(DiffKind::Same, l))
+        .collect();
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        out.extend(a.iter().map(|l| line(DiffKind::Removed, l)));
+        out.extend(b.iter().map(|l| line(DiffKind::Added, l)));
+    } else {
+        // lcs[i][j]: length of the longest common subsequence of a[i..] and b[j..].
+        let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i][j] = if a[i] == b[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                out.push(line(DiffKind::Same, a[i]));
+                i += 1;
+                j += 1;
+            } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                out.push(line(DiffKind::Removed, a[i]));
+                i += 1;
+            } else {
+                out.push(line(DiffKind::Added, b[j]));
+                j += 1;
+            }
+        }
+    }
+    out.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|l| line(DiffKind::Same, l)),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_diff_lines_marks_added_and_removed_lines() {
+        let diff = diff_lines("one\ntwo\nthree\nfour", "one\n2\nthree\nfour\nfive");
+        let rendered: Vec<String> = diff
+            .iter()
+            .map(|l| format!("{}{}", l.marker(), l.text))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![" one", "-two", "+2", " three", " four", "+five"]
+        );
+
+        assert!(diff_lines("same\ntext", "same\ntext")
+            .iter()
+            .all(|l| l.kind == DiffKind::Same));
+        assert_eq!(diff_lines("", "new").len(), 1);
+    }
+
+    #[test]
+    fn test_parse_tags_normalizes_and_dedupes() {
+        assert_eq!(
+            parse_tags(" Rust, #async io,rust ,, RUST").unwrap(),
+            vec!["rust", "async-io"]
+        );
+        assert_eq!(parse_tags("").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_tags(" , ,").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parse_tags_enforces_limits() {
+        assert!(parse_tags("a,b,c,d,e").is_ok());
+        assert!(parse_tags("a,b,c,d,e,f").is_err());
+        assert!(parse_tags(&"x".repeat(MAX_TAG_CHARS)).is_ok());
+        assert!(parse_tags(&"x".repeat(MAX_TAG_CHARS + 1)).is_err());
+        assert!(parse_tags("c++").is_err());
+        assert!(parse_tags("tag/../admin").is_err());
+    }
+
+    #[test]
+    fn test_parse_report_reason() {
+        assert_eq!(parse_report_reason("  spam link  ").unwrap(), "spam link");
+        assert!(parse_report_reason(" \n ").is_err());
+        assert!(parse_report_reason(&"x".repeat(MAX_REPORT_REASON_CHARS)).is_ok());
+        assert!(parse_report_reason(&"x".repeat(MAX_REPORT_REASON_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_is_unread() {
+        let seen = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+
+        assert!(is_unread(seen + Duration::seconds(1), Some(seen)));
+        assert!(!is_unread(seen, Some(seen)));
+        assert!(!is_unread(seen - Duration::days(1), Some(seen)));
+        assert!(is_unread(seen - Duration::days(365), None));
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let ago = |d: Duration| relative_time(now - d, now);
+
+        assert_eq!(ago(Duration::seconds(30)), "just now");
+        assert_eq!(ago(Duration::minutes(1)), "1 minute ago");
+        assert_eq!(ago(Duration::minutes(59)), "59 minutes ago");
+        assert_eq!(ago(Duration::hours(2)), "2 hours ago");
+        assert_eq!(ago(Duration::days(1)), "1 day ago");
+        assert_eq!(ago(Duration::days(45)), "on May 01, 2024");
+        // Clock skew shouldn't produce negative durations.
+        assert_eq!(ago(Duration::seconds(-5)), "just now");
+    }
+
+    #[test]
+    fn test_edited_after_ignores_quick_saves() {
+        let created = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+
+        assert!(!edited_after(created, created));
+        assert!(!edited_after(created, created + Duration::seconds(60)));
+        assert!(edited_after(created, created + Duration::seconds(61)));
+    }
+
+    #[test]
+    fn test_normalize_display_name() {
+        assert_eq!(
+            normalize_display_name("  Ferris \u{7}Crab ").unwrap(),
+            Some("Ferris Crab".to_string())
+        );
+        assert_eq!(normalize_display_name("   ").unwrap(), None);
+        assert_eq!(
+            normalize_display_name("a\nb").unwrap(),
+            Some("ab".to_string())
+        );
+        assert!(normalize_display_name(&"x".repeat(MAX_DISPLAY_NAME_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_bio_keeps_line_breaks() {
+        assert_eq!(
+            normalize_bio("\r\n Rustacean.\r\nLikes \u{1b}[31mcrabs\n\n").unwrap(),
+            Some("Rustacean.\nLikes [31mcrabs".to_string())
+        );
+        assert_eq!(normalize_bio("\n\t ").unwrap(), None);
+        assert!(normalize_bio(&"é".repeat(MAX_BIO_CHARS)).is_ok());
+        assert!(normalize_bio(&"é".repeat(MAX_BIO_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_slugify_handles_unicode_and_degenerate_titles() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  Rust 1.75 -- released  "), "rust-1-75-released");
+        assert_eq!(slugify("Café über Straße"), "café-über-straße");
+        assert_eq!(slugify("日本語のタイトル"), "日本語のタイトル");
+        assert_eq!(slugify("snake_case\ttabs"), "snake-case-tabs");
+        assert_eq!(slugify(""), "post");
+        assert_eq!(slugify("!!! ???"), "post");
+        assert_eq!(slugify("🎉🎉"), "post");
+
+        let long = slugify(&format!("{} tail", "a".repeat(MAX_SLUG_CHARS - 1)));
+        assert_eq!(long, "a".repeat(MAX_SLUG_CHARS - 1));
+    }
+
+    #[test]
+    fn test_unique_slug_picks_the_first_free_suffix() {
+        let taken = |slugs: &[&str]| slugs.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(unique_slug("hello", &[]), "hello");
+        assert_eq!(unique_slug("hello", &taken(&["hello-2"])), "hello");
+        assert_eq!(unique_slug("hello", &taken(&["hello"])), "hello-2");
+        assert_eq!(
+            unique_slug(
+                "hello",
+                &taken(&["hello", "hello-2", "hello-world", "hello-4"])
+            ),
+            "hello-3"
+        );
+    }
+
+    #[test]
+    fn test_validate_board_slug() {
+        assert!(validate_board_slug("rust").is_ok());
+        assert!(validate_board_slug("off-topic-2").is_ok());
+
+        for bad in [
+            "",
+            "Rust",
+            "off topic",
+            "-rust",
+            "rust-",
+            "off--topic",
+            "ünïcode",
+            "a/b",
+        ] {
+            assert!(validate_board_slug(bad).is_err(), "{:?}", bad);
+        }
+        assert!(validate_board_slug(&"a".repeat(MAX_BOARD_FIELD_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_validate_board_name() {
+        assert!(validate_board_name("General Discussion").is_ok());
+        assert!(validate_board_name("   ").is_err());
+        assert!(validate_board_name(&"n".repeat(MAX_BOARD_FIELD_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_title_limit_boundary() {
+        assert!(validate_post_title(&"a".repeat(MAX_POST_TITLE_CHARS)).is_ok());
+        assert!(validate_post_title(&"a".repeat(MAX_POST_TITLE_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_content_limit_boundary() {
+        assert!(validate_post_content(&"a".repeat(MAX_POST_CONTENT_CHARS)).is_ok());
+        assert!(validate_post_content(&"a".repeat(MAX_POST_CONTENT_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_post_text_rejects_nul_and_control_characters() {
+        assert_eq!(
+            validate_post_title("Hello\0world"),
+            Err("Text must not contain NUL characters".to_string())
+        );
+        assert!(validate_post_title("Bell\x07").is_err());
+        assert!(validate_post_content("C1 \u{9b}31m").is_err());
+        assert!(validate_post_content("Line one\r\n\tLine two").is_ok());
+        assert!(validate_post_title("Launch day 🚀🦀").is_ok());
+    }
+
+    #[test]
+    fn test_limits_count_characters_not_bytes() {
+        let title = "é".repeat(MAX_POST_TITLE_CHARS);
+        assert!(title.len() > MAX_POST_TITLE_CHARS);
+        assert!(validate_post_title(&title).is_ok());
+
+        let content = "文".repeat(MAX_POST_CONTENT_CHARS);
+        assert!(validate_post_content(&content).is_ok());
+        assert!(validate_post_content(&format!("{}文", content)).is_err());
+    }
+
+    #[test]
+    fn test_online_user_connected_for() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap();
+        let since = |minutes| OnlineUser {
+            username: None,
+            via: "ssh",
+            connected_at: now - Duration::minutes(minutes),
+        };
+
+        assert_eq!(since(0).connected_for(now), "0m");
+        assert_eq!(since(59).connected_for(now), "59m");
+        assert_eq!(since(125).connected_for(now), "2h 05m");
+        assert_eq!(since(3 * 1440 + 61).connected_for(now), "3d 1h");
+        assert_eq!(since(-5).connected_for(now), "0m");
+        assert_eq!(since(0).shown_name(), "guest");
+    }
+}
@@ -0,0 +1,126 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long a registration challenge stays valid after it is issued.
+pub const CHALLENGE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Outstanding challenges kept before the oldest are dropped.
+const MAX_OUTSTANDING: usize = 10_000;
+
+/// Checks a hashcash-style solution: `SHA-256(challenge || nonce)` must start
+/// with at least `difficulty` zero bits.
+pub fn verify_pow(challenge: &str, nonce: &str, difficulty: u32) -> bool {
+    let digest = Sha256::new()
+        .chain_update(challenge.as_bytes())
+        .chain_update(nonce.as_bytes())
+        .finalize();
+    leading_zero_bits(&digest) >= difficulty
+}
+
+fn leading_zero_bits(bytes: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in bytes {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Challenges handed out by the registration form. Each can be redeemed once
+/// and only within `CHALLENGE_TTL`, so a solved challenge can't be replayed
+/// to create a batch of accounts.
+#[derive(Debug)]
+pub struct ChallengeStore {
+    ttl: Duration,
+    issued: Mutex<HashMap<String, Instant>>,
+}
+
+impl Default for ChallengeStore {
+    fn default() -> Self {
+        Self::new(CHALLENGE_TTL)
+    }
+}
+
+impl ChallengeStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            issued: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn issue(&self) -> String {
+        let now = Instant::now();
+        let challenge = Uuid::new_v4().simple().to_string();
+        let mut issued = self.issued.lock().unwrap();
+
+        issued.retain(|_, at| now.duration_since(*at) < self.ttl);
+        if issued.len() >= MAX_OUTSTANDING {
+            if let Some(oldest) = issued
+                .iter()
+                .min_by_key(|(_, at)| **at)
+                .map(|(c, _)| c.clone())
+            {
+                issued.remove(&oldest);
+            }
+        }
+        issued.insert(challenge.clone(), now);
+
+        challenge
+    }
+
+    /// Consumes `challenge`, returning whether it was issued here and is still fresh.
+    pub fn redeem(&self, challenge: &str) -> bool {
+        let mut issued = self.issued.lock().unwrap();
+        issued
+            .remove(challenge)
+            .is_some_and(|at| at.elapsed() < self.ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(challenge: &str, difficulty: u32) -> String {
+        (0u64..)
+            .map(|n| n.to_string())
+            .find(|nonce| verify_pow(challenge, nonce, difficulty))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0xff]), 0);
+        assert_eq!(leading_zero_bits(&[0x00, 0x10]), 11);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00]), 16);
+    }
+
+    #[test]
+    fn test_verify_pow() {
+        let nonce = solve("challenge", 12);
+        assert!(verify_pow("challenge", &nonce, 12));
+        assert!(verify_pow("challenge", &nonce, 0));
+
+        // Known vector: SHA-256("abc") starts with 0xba, so no leading zeros.
+        assert!(verify_pow("ab", "c", 0));
+        assert!(!verify_pow("ab", "c", 1));
+    }
+
+    #[test]
+    fn test_challenges_are_single_use_and_expire() {
+        let store = ChallengeStore::default();
+        let challenge = store.issue();
+        assert!(store.redeem(&challenge));
+        assert!(!store.redeem(&challenge));
+        assert!(!store.redeem("never-issued"));
+
+        let store = ChallengeStore::new(Duration::ZERO);
+        let challenge = store.issue();
+        assert!(!store.redeem(&challenge));
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{db, models::OnlineUser};
+
+/// Web users count as online for this long after their session was last used.
+pub const WEB_ONLINE_WINDOW_MINUTES: i64 = 15;
+
+/// SSH clients that are past the login screen, keyed by client id. Shared
+/// between the SSH server, which keeps it current, and the web front end.
+#[derive(Default)]
+pub struct Presence {
+    ssh: Mutex<HashMap<usize, OnlineUser>>,
+}
+
+impl Presence {
+    /// Records that SSH client `client_id` is signed in (or browsing as a
+    /// guest). Calling it again for the same client replaces the entry.
+    pub fn connect(&self, client_id: usize, username: Option<String>, since: DateTime<Utc>) {
+        self.ssh.lock().unwrap().insert(
+            client_id,
+            OnlineUser {
+                username,
+                via: "ssh",
+                connected_at: since,
+            },
+        );
+    }
+
+    pub fn disconnect(&self, client_id: usize) {
+        self.ssh.lock().unwrap().remove(&client_id);
+    }
+
+    pub fn ssh_users(&self) -> Vec<OnlineUser> {
+        self.ssh.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Everyone online: SSH clients plus recently active web users, longest
+    /// connected first.
+    pub async fn online_users(&self, db: &PgPool) -> crate::Result<Vec<OnlineUser>> {
+        let since = Utc::now() - Duration::minutes(WEB_ONLINE_WINDOW_MINUTES);
+        let mut users = db::active_web_users(db, since).await?;
+        users.extend(self.ssh_users());
+        users.sort_by_key(|user| user.connected_at);
+        Ok(users)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disconnect_removes_only_that_client() {
+        let presence = Presence::default();
+        let now = Utc::now();
+        presence.connect(1, Some("alice".to_string()), now);
+        presence.connect(2, None, now);
+        presence.connect(2, None, now);
+
+        assert_eq!(presence.ssh_users().len(), 2);
+
+        presence.disconnect(1);
+        presence.disconnect(3);
+
+        let users = presence.ssh_users();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].shown_name(), "guest");
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_ATTEMPTS: usize = 5;
+const DEFAULT_WINDOW: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Default)]
+struct Attempts {
+    recent: Vec<Instant>,
+    locked_until: Option<Instant>,
+}
+
+/// Counts attempts per key (failed logins or abuse reports per client IP,
+/// new posts per user, ...) and locks out keys that make too many within the
+/// window.
+#[derive(Debug)]
+pub struct RateLimiter<K = IpAddr> {
+    max_attempts: usize,
+    window: Duration,
+    lockout: Duration,
+    attempts: Mutex<HashMap<K, Attempts>>,
+}
+
+impl<K: Eq + Hash + Copy + Display> Default for RateLimiter<K> {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ATTEMPTS, DEFAULT_WINDOW, DEFAULT_LOCKOUT)
+    }
+}
+
+impl<K: Eq + Hash + Copy + Display> RateLimiter<K> {
+    pub fn new(max_attempts: usize, window: Duration, lockout: Duration) -> Self {
+        Self {
+            max_attempts,
+            window,
+            lockout,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns how long `key` remains locked out, or `None` if it may make an attempt.
+    pub fn time_until_allowed(&self, key: K) -> Option<Duration> {
+        let attempts = self.attempts.lock().unwrap();
+        Self::remaining(&attempts, key, Instant::now())
+    }
+
+    pub fn record(&self, key: K) {
+        let mut attempts = self.attempts.lock().unwrap();
+        self.record_at(&mut attempts, key, Instant::now());
+    }
+
+    /// Records an attempt by `key` unless it is locked out, in which case the
+    /// remaining lockout is returned and nothing is recorded. Checking and
+    /// recording under one lock keeps concurrent requests from slipping past
+    /// the limit together.
+    pub fn try_record(&self, key: K) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut attempts = self.attempts.lock().unwrap();
+        if let Some(remaining) = Self::remaining(&attempts, key, now) {
+            return Err(remaining);
+        }
+        self.record_at(&mut attempts, key, now);
+        Ok(())
+    }
+
+    pub fn reset(&self, key: K) {
+        self.attempts.lock().unwrap().remove(&key);
+    }
+
+    fn remaining(attempts: &HashMap<K, Attempts>, key: K, now: Instant) -> Option<Duration> {
+        attempts
+            .get(&key)
+            .and_then(|a| a.locked_until)
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    fn record_at(&self, attempts: &mut HashMap<K, Attempts>, key: K, now: Instant) {
+        let window = self.window;
+        attempts.retain(|_, a| {
+            a.locked_until.is_some_and(|until| until > now)
+                || a.recent.iter().any(|t| now.duration_since(*t) < window)
+        });
+
+        let entry = attempts.entry(key).or_default();
+        if entry.locked_until.is_some_and(|until| until <= now) {
+            entry.locked_until = None;
+            entry.recent.clear();
+        }
+        entry.recent.retain(|t| now.duration_since(*t) < window);
+        entry.recent.push(now);
+
+        if entry.recent.len() >= self.max_attempts {
+            tracing::warn!("Locking out {} after {} attempts", key, entry.recent.len());
+            entry.locked_until = Some(now + self.lockout);
+            entry.recent.clear();
+        }
+    }
+}
+
+/// User-facing message shown when a login is rejected by the rate limiter.
+pub fn lockout_message(remaining: Duration) -> String {
+    let minutes = remaining.as_secs().div_ceil(60).max(1);
+    format!(
+        "Too many failed login attempts. Try again in {} minute{}.",
+        minutes,
+        if minutes == 1 { "" } else { "s" }
+    )
+}
+
+/// A wait in the largest two units that matter, rounded up so it never
+/// reads as zero: "45s", "2m 5s", "1h 30m".
+pub fn short_duration(duration: Duration) -> String {
+    let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    let secs = secs.max(1);
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    match (hours, minutes, seconds) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}m", m),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, 0, _) => format!("{}h", h),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
+
+/// Shown instead of the post form's usual error while a user is throttled.
+pub fn cooldown_message(remaining: Duration) -> String {
+    format!("You can post again in {}.", short_duration(remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip() -> IpAddr {
+        IpAddr::from([192, 0, 2, 1])
+    }
+
+    #[test]
+    fn test_locks_after_max_attempts() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60), Duration::from_secs(60));
+
+        for _ in 0..2 {
+            limiter.record(ip());
+            assert!(limiter.time_until_allowed(ip()).is_none());
+        }
+
+        limiter.record(ip());
+        assert!(limiter.time_until_allowed(ip()).is_some());
+        assert!(limiter
+            .time_until_allowed(IpAddr::from([192, 0, 2, 2]))
+            .is_none());
+    }
+
+    #[test]
+    fn test_reset_clears_failures() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60), Duration::from_secs(60));
+
+        limiter.record(ip());
+        limiter.reset(ip());
+        limiter.record(ip());
+
+        assert!(limiter.time_until_allowed(ip()).is_none());
+    }
+
+    #[test]
+    fn test_lockout_expires() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60), Duration::ZERO);
+
+        limiter.record(ip());
+
+        assert!(limiter.time_until_allowed(ip()).is_none());
+    }
+
+    #[test]
+    fn test_try_record_allows_up_to_the_limit() {
+        let limiter: RateLimiter<u32> =
+            RateLimiter::new(3, Duration::from_secs(60), Duration::from_secs(60));
+
+        for _ in 0..3 {
+            assert!(limiter.try_record(7).is_ok());
+        }
+        assert!(limiter.try_record(7).is_err());
+        assert!(limiter.try_record(8).is_ok());
+    }
+
+    #[test]
+    fn test_time_until_allowed_counts_down_from_the_lockout() {
+        let limiter: RateLimiter<u32> =
+            RateLimiter::new(2, Duration::from_secs(60), Duration::from_secs(90));
+        let start = Instant::now();
+        let mut attempts = limiter.attempts.lock().unwrap();
+
+        limiter.record_at(&mut attempts, 7, start);
+        assert_eq!(RateLimiter::remaining(&attempts, 7, start), None);
+
+        limiter.record_at(&mut attempts, 7, start);
+        assert_eq!(
+            RateLimiter::remaining(&attempts, 7, start + Duration::from_secs(30)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            RateLimiter::remaining(&attempts, 7, start + Duration::from_secs(90)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_short_duration_and_cooldown_message() {
+        assert_eq!(short_duration(Duration::from_millis(200)), "1s");
+        assert_eq!(short_duration(Duration::from_millis(44_100)), "45s");
+        assert_eq!(short_duration(Duration::from_secs(120)), "2m");
+        assert_eq!(short_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(short_duration(Duration::from_secs(3600)), "1h");
+        assert_eq!(short_duration(Duration::from_secs(5430)), "1h 30m");
+        assert_eq!(
+            cooldown_message(Duration::from_secs(45)),
+            "You can post again in 45s."
+        );
+    }
+
+    #[test]
+    fn test_lockout_message() {
+        assert_eq!(
+            lockout_message(Duration::from_secs(30)),
+            "Too many failed login attempts. Try again in 1 minute."
+        );
+        assert_eq!(
+            lockout_message(Duration::from_secs(15 * 60)),
+            "Too many failed login attempts. Try again in 15 minutes."
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Keeps a client-supplied id if it is short and printable, so a proxy's id
+/// can be followed end to end without letting callers stuff the logs.
+fn inbound_request_id(request: &Request) -> Option<String> {
+    let id = request.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?;
+    let valid = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| id.to_string())
+}
+
+/// Tags every log line emitted while handling a request with its id and
+/// echoes the id back in the `X-Request-Id` response header.
+pub async fn request_id(request: Request, next: Next) -> Response {
+    let id = inbound_request_id(&request).unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    // Nests inside the HTTP trace span, which already records method and URI.
+    let span = tracing::info_span!("request_id", id = %id);
+    let mut response = next.run(request).instrument(span).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    response
+}
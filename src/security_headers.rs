@@ -0,0 +1,59 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+/// Allows our own origin, gravatar images, and the CDN the stylesheets and
+/// editor load from. Inline scripts and styles are still used by a few pages.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    script-src 'self' 'unsafe-inline' https://cdn.jsdelivr.net; \
+    style-src 'self' 'unsafe-inline' https://cdn.jsdelivr.net https://maxcdn.bootstrapcdn.com; \
+    font-src 'self' https://maxcdn.bootstrapcdn.com; \
+    img-src 'self' data: https://www.gravatar.com; \
+    connect-src 'self'; \
+    frame-ancestors 'none'; \
+    base-uri 'self'; \
+    form-action 'self'";
+
+/// Operator-supplied CSP, added as a request extension ahead of
+/// [`security_headers`]. Without it the default policy is sent.
+#[derive(Clone, Debug)]
+pub struct ContentSecurityPolicy(pub HeaderValue);
+
+impl ContentSecurityPolicy {
+    pub fn parse(policy: &str) -> crate::Result<Self> {
+        HeaderValue::from_str(policy).map(Self).map_err(|_| {
+            crate::Error::Internal(format!("Invalid Content-Security-Policy: {:?}", policy))
+        })
+    }
+}
+
+/// Adds anti-framing, MIME sniffing, referrer and content security headers
+/// to every response. Headers a handler already set are left alone.
+pub async fn security_headers(request: Request, next: Next) -> Response {
+    let csp = request
+        .extensions()
+        .get::<ContentSecurityPolicy>()
+        .map(|csp| csp.0.clone())
+        .unwrap_or_else(|| HeaderValue::from_static(DEFAULT_CONTENT_SECURITY_POLICY));
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    for (name, value) in [
+        (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+        (
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        ),
+        (header::CONTENT_SECURITY_POLICY, csp),
+        (
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("strict-origin-when-cross-origin"),
+        ),
+    ] {
+        headers.entry(name).or_insert(value);
+    }
+    response
+}
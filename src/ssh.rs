@@ -1,9 +1,49 @@
+mod exec;
+mod motd;
 mod server;
 mod terminal;
 mod ui;
 
 use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
-pub async fn serve(addr: String, db: PgPool) -> crate::Result<()> {
-    server::run_ssh_server(addr, db).await
+use crate::{
+    auth::PasswordParams, maintenance::Maintenance, models::Post, presence::Presence,
+    ratelimit::RateLimiter,
+};
+
+/// Listener settings for the SSH server, taken from `Config`.
+pub struct SshOptions {
+    pub addr: String,
+    pub host_key_path: String,
+    pub inactivity_timeout: Duration,
+    pub max_sessions: usize,
+    pub motd_path: String,
+    /// Username accepted as a guest; `None` disables guest login.
+    pub guest_username: Option<String>,
+    /// Posts per page of the post list.
+    pub page_size: usize,
+    /// Used when password logins upgrade weak hashes.
+    pub password_params: PasswordParams,
+    /// Read-only switch, shared with the web front end.
+    pub maintenance: Arc<Maintenance>,
+    /// Close sessions after this long without a keypress; `None` never does.
+    pub idle_logout: Option<Duration>,
+    /// New posts per user, shared with the web front end.
+    pub post_limiter: Arc<RateLimiter<Uuid>>,
+}
+
+pub async fn serve(
+    options: SshOptions,
+    db: PgPool,
+    login_limiter: Arc<RateLimiter>,
+    post_events: broadcast::Sender<Post>,
+    presence: Arc<Presence>,
+    shutdown: CancellationToken,
+) -> crate::Result<()> {
+    server::run_ssh_server(options, db, login_limiter, post_events, presence, shutdown).await
 }
@@ -0,0 +1,286 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::ui::sanitize_for_terminal;
+use crate::models::MAX_POST_CONTENT_CHARS;
+
+/// Printed for `help`, an empty command, and anything unrecognised.
+pub const USAGE: &str = "\
+Commands:
+  list           recent posts, one per line: id, date, author, title
+  read <id>      print a post
+  post <title>   publish a post, reading its body from stdin
+  help           show this message
+";
+
+/// Why `post` is refused to guests.
+pub const GUEST_POST_MESSAGE: &str =
+    "Guests can only list and read posts. Log in with your SSH key to post.";
+
+/// Largest body `post` buffers, in bytes: the character limit at four bytes
+/// per character.
+const MAX_BODY_BYTES: usize = MAX_POST_CONTENT_CHARS * 4;
+
+/// A command given as `ssh user@host <command>`, run without the TUI.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExecCommand {
+    Help,
+    List,
+    Read(Uuid),
+    /// Publishes a post; the body follows on stdin.
+    Post {
+        title: String,
+    },
+}
+
+impl ExecCommand {
+    /// Guests may look but not post.
+    pub fn allowed_for_guests(&self) -> bool {
+        !matches!(self, ExecCommand::Post { .. })
+    }
+}
+
+/// Parses the command line, or explains what is wrong with it.
+pub fn parse(command: &str) -> Result<ExecCommand, String> {
+    let command = command.trim();
+    let (name, args) = match command.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => (command, ""),
+    };
+
+    match (name, args) {
+        ("" | "help", _) => Ok(ExecCommand::Help),
+        ("list", "") => Ok(ExecCommand::List),
+        ("list", _) => Err("list takes no arguments".to_string()),
+        ("read", "") => Err("Usage: read <id>".to_string()),
+        ("read", id) => id
+            .parse()
+            .map(ExecCommand::Read)
+            .map_err(|_| format!("Not a post id: {}", id)),
+        ("post", "") => Err("Usage: post <title>, with the body on stdin".to_string()),
+        ("post", title) => Ok(ExecCommand::Post {
+            title: title.to_string(),
+        }),
+        (name, _) => Err(format!("Unknown command: {}\n\n{}", name, USAGE)),
+    }
+}
+
+/// What an exec command sends back before the channel closes.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reply {
+    /// Written to stdout, exit status 0.
+    Output(String),
+    /// Written to stderr, exit status 1.
+    Failure(String),
+    /// Nothing yet: the body of a `post` is still being read.
+    ReadingBody,
+}
+
+/// A `post` whose body is arriving on stdin.
+#[derive(Clone)]
+pub struct PendingPost {
+    pub title: String,
+    body: Vec<u8>,
+    too_long: bool,
+}
+
+impl PendingPost {
+    pub fn new(title: String) -> Self {
+        Self {
+            title,
+            body: Vec::new(),
+            too_long: false,
+        }
+    }
+
+    /// Appends stdin data, dropping everything once the body is too long to
+    /// ever be accepted.
+    pub fn push(&mut self, data: &[u8]) {
+        if self.too_long || self.body.len() + data.len() > MAX_BODY_BYTES {
+            self.too_long = true;
+            self.body.clear();
+        } else {
+            self.body.extend_from_slice(data);
+        }
+    }
+
+    /// The body once stdin is closed; `None` if it was cut off for length.
+    pub fn body(&self) -> Option<String> {
+        (!self.too_long).then(|| String::from_utf8_lossy(&self.body).into_owned())
+    }
+}
+
+/// A row of `list`.
+pub struct ListedPost {
+    pub id: Uuid,
+    pub title: String,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A post as `read` prints it.
+pub struct PostText {
+    pub id: Uuid,
+    pub title: String,
+    pub content: String,
+    pub author: String,
+    pub board: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// A field from another user, made safe for the terminal and kept on one
+/// line so it cannot add rows or columns to the output.
+fn one_line(text: &str) -> String {
+    sanitize_for_terminal(text).replace(['\t', '\n'], " ")
+}
+
+/// Tab-separated so scripts can split it; one post per line.
+pub fn format_list(posts: &[ListedPost]) -> String {
+    posts
+        .iter()
+        .map(|post| {
+            format!(
+                "{}\t{}\t{}\t{}\n",
+                post.id,
+                timestamp(post.created_at),
+                one_line(&post.author),
+                one_line(&post.title)
+            )
+        })
+        .collect()
+}
+
+pub fn format_post(post: &PostText) -> String {
+    let board = post
+        .board
+        .as_deref()
+        .map(|board| format!(" in {}", one_line(board)))
+        .unwrap_or_default();
+    let mut text = format!(
+        "{}\nby {} on {} UTC{}\nid: {}\n\n{}",
+        one_line(&post.title),
+        one_line(&post.author),
+        timestamp(post.created_at),
+        board,
+        post.id,
+        sanitize_for_terminal(&post.content)
+    );
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_commands() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(parse(""), Ok(ExecCommand::Help));
+        assert_eq!(parse("  help "), Ok(ExecCommand::Help));
+        assert_eq!(parse("list"), Ok(ExecCommand::List));
+        assert_eq!(parse(&format!("read {}", id)), Ok(ExecCommand::Read(id)));
+        assert_eq!(
+            parse("post  Hello there "),
+            Ok(ExecCommand::Post {
+                title: "Hello there".to_string()
+            })
+        );
+
+        assert!(parse("read").is_err());
+        assert!(parse("read 42").unwrap_err().contains("Not a post id"));
+        assert!(parse("post").is_err());
+        assert!(parse("list everything").is_err());
+        let unknown = parse("rm -rf /").unwrap_err();
+        assert!(unknown.contains("Unknown command: rm"));
+        assert!(unknown.contains("read <id>"));
+    }
+
+    #[test]
+    fn test_guests_may_only_look() {
+        assert!(ExecCommand::List.allowed_for_guests());
+        assert!(ExecCommand::Read(Uuid::new_v4()).allowed_for_guests());
+        assert!(!ExecCommand::Post {
+            title: "Hi".to_string()
+        }
+        .allowed_for_guests());
+    }
+
+    #[test]
+    fn test_pending_post_gives_up_on_oversized_bodies() {
+        let mut post = PendingPost::new("Title".to_string());
+        post.push(b"first ");
+        post.push(b"second");
+        assert_eq!(post.body().as_deref(), Some("first second"));
+
+        post.push(&vec![b'x'; MAX_BODY_BYTES]);
+        post.push(b"more");
+        assert_eq!(post.body(), None);
+    }
+
+    #[test]
+    fn test_format_list_and_post() {
+        let id = Uuid::new_v4();
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap();
+
+        let list = format_list(&[ListedPost {
+            id,
+            title: "Hello".to_string(),
+            author: "alice".to_string(),
+            created_at,
+        }]);
+        assert_eq!(list, format!("{}\t2024-03-01 09:30\talice\tHello\n", id));
+
+        let post = format_post(&PostText {
+            id,
+            title: "Hello".to_string(),
+            content: "Body".to_string(),
+            author: "alice".to_string(),
+            board: Some("General".to_string()),
+            created_at,
+        });
+        assert_eq!(
+            post,
+            format!(
+                "Hello\nby alice on 2024-03-01 09:30 UTC in General\nid: {}\n\nBody\n",
+                id
+            )
+        );
+    }
+
+    #[test]
+    fn test_output_cannot_drive_the_terminal() {
+        let id = Uuid::new_v4();
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap();
+
+        let list = format_list(&[ListedPost {
+            id,
+            title: "Evil\x1b[2J\ttitle\nsecond row".to_string(),
+            author: "mallory\x1b]0;pwned\x07".to_string(),
+            created_at,
+        }]);
+        assert!(!list.contains('\x1b'));
+        assert_eq!(list.matches('\t').count(), 3);
+        assert_eq!(list.matches('\n').count(), 1);
+        assert!(list.contains("Evil^[[2J title second row"));
+
+        let post = format_post(&PostText {
+            id,
+            title: "Evil\x1b[2J".to_string(),
+            content: "Line one\x1b[2J\nLine two".to_string(),
+            author: "mallory".to_string(),
+            board: Some("Gen\x1b[2Jeral".to_string()),
+            created_at,
+        });
+        assert!(!post.contains('\x1b'));
+        assert!(post.contains("Line one^[[2J\nLine two\n"));
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio::sync::Mutex;
+
+/// Most banner lines shown on the login screen.
+pub const MAX_MOTD_LINES: usize = 12;
+
+/// File metadata used to notice that the banner changed on disk.
+type Stamp = (Option<SystemTime>, u64);
+
+/// The message of the day shown above the SSH login prompt.
+///
+/// The file is read once at startup and again whenever its modification time
+/// or size changes, so operators can edit it without restarting the server.
+pub struct Motd {
+    path: PathBuf,
+    cached: Mutex<Option<(Stamp, Option<String>)>>,
+}
+
+impl Motd {
+    pub async fn load(path: impl Into<PathBuf>) -> Self {
+        let motd = Self {
+            path: path.into(),
+            cached: Mutex::new(None),
+        };
+        if motd.current().await.is_none() {
+            tracing::info!(
+                "No SSH MOTD at {}; using the default title",
+                motd.path.display()
+            );
+        }
+        motd
+    }
+
+    /// The banner text, or `None` if the file is missing, unreadable or blank.
+    pub async fn current(&self) -> Option<String> {
+        let Ok(metadata) = tokio::fs::metadata(&self.path).await else {
+            *self.cached.lock().await = None;
+            return None;
+        };
+        let stamp = (metadata.modified().ok(), metadata.len());
+
+        let mut cached = self.cached.lock().await;
+        if let Some((cached_stamp, text)) = cached.as_ref() {
+            if *cached_stamp == stamp {
+                return text.clone();
+            }
+        }
+
+        let text = match tokio::fs::read_to_string(&self.path).await {
+            Ok(raw) => clean(&raw),
+            Err(e) => {
+                tracing::warn!("Failed to read SSH MOTD {}: {}", self.path.display(), e);
+                None
+            }
+        };
+        *cached = Some((stamp, text.clone()));
+        text
+    }
+}
+
+/// Drops control characters (a stray escape sequence would garble the
+/// client's terminal), trailing blank lines, and anything past `MAX_MOTD_LINES`.
+fn clean(raw: &str) -> Option<String> {
+    let lines: Vec<String> = raw
+        .lines()
+        .take(MAX_MOTD_LINES)
+        .map(|line| line.chars().filter(|c| !c.is_control()).collect())
+        .collect();
+    let text = lines.join("\n");
+    let text = text.trim_end();
+    (!text.trim().is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_strips_control_characters_and_limits_lines() {
+        assert_eq!(
+            clean("\x1b[31mWelcome\x07 caller\r\n\n\n").as_deref(),
+            Some("[31mWelcome caller")
+        );
+        assert_eq!(clean(" \n\t\n"), None);
+
+        let long = (0..20)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(clean(&long).unwrap().lines().count(), MAX_MOTD_LINES);
+    }
+
+    #[tokio::test]
+    async fn test_motd_reloads_when_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("iron-bbs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("motd.txt");
+
+        let motd = Motd::load(&path).await;
+        assert_eq!(motd.current().await, None);
+
+        std::fs::write(&path, "Welcome to the board").unwrap();
+        assert_eq!(
+            motd.current().await.as_deref(),
+            Some("Welcome to the board")
+        );
+
+        std::fs::write(&path, "Maintenance tonight at 22:00").unwrap();
+        assert_eq!(
+            motd.current().await.as_deref(),
+            Some("Maintenance tonight at 22:00")
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(motd.current().await, None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
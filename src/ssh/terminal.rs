@@ -19,6 +19,11 @@ impl TerminalHandle {
                 }
             }
         });
+        Self::with_sender(sender)
+    }
+
+    /// A handle whose flushed frames go to `sender` instead of a channel.
+    pub fn with_sender(sender: UnboundedSender<Vec<u8>>) -> Self {
         Self {
             sender,
             sink: Vec::new(),
@@ -26,6 +31,15 @@ impl TerminalHandle {
     }
 }
 
+impl Clone for TerminalHandle {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            sink: Vec::new(),
+        }
+    }
+}
+
 impl io::Write for TerminalHandle {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.sink.extend_from_slice(buf);
@@ -33,11 +47,51 @@ impl io::Write for TerminalHandle {
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        let result = self.sender.send(self.sink.clone());
-        if let Err(e) = result {
-            return Err(io::Error::new(io::ErrorKind::BrokenPipe, e));
+        if self.sink.is_empty() {
+            return Ok(());
         }
-        self.sink.clear();
-        Ok(())
+
+        // Hand the buffer over instead of copying it; the next frame starts empty.
+        let frame = std::mem::take(&mut self.sink);
+        self.sender
+            .send(frame)
+            .map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    fn handle() -> (TerminalHandle, UnboundedReceiver<Vec<u8>>) {
+        let (sender, receiver) = unbounded_channel();
+        (TerminalHandle::with_sender(sender), receiver)
+    }
+
+    #[test]
+    fn test_flush_sends_buffered_frame() {
+        let (mut terminal, mut receiver) = handle();
+
+        terminal.write_all(b"hello ").unwrap();
+        terminal.write_all(b"world").unwrap();
+        terminal.flush().unwrap();
+
+        assert_eq!(receiver.try_recv().unwrap(), b"hello world");
+        assert!(terminal.sink.is_empty());
+    }
+
+    #[test]
+    fn test_empty_flush_is_a_no_op() {
+        let (mut terminal, mut receiver) = handle();
+
+        terminal.flush().unwrap();
+        terminal.write_all(b"x").unwrap();
+        terminal.flush().unwrap();
+        terminal.flush().unwrap();
+
+        assert_eq!(receiver.try_recv().unwrap(), b"x");
+        assert!(receiver.try_recv().is_err());
     }
 }
@@ -1,17 +1,209 @@
-use crate::models::Post;
+use crate::models::{is_unread, Board, OnlineUser, Post};
+use chrono::{DateTime, Utc};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppState {
     Login,
     SecurityAlert,
+    BoardList,
     Browsing,
+    Searching,
+    Editing,
+    Viewing,
+    Composing,
+    Help,
+    Online,
+}
+
+/// Sections and key/action pairs shown in the help overlay.
+const HELP_SECTIONS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Boards",
+        &[
+            ("↑ / k, ↓ / j", "Choose board"),
+            ("Enter", "Open board"),
+            ("t", "Switch colour theme"),
+        ],
+    ),
+    (
+        "Browsing",
+        &[
+            ("↑ / k", "Previous post"),
+            ("↓ / j", "Next post (past the last: next page)"),
+            ("] / PgDn", "Next page"),
+            ("[ / PgUp", "Previous page"),
+            ("s", "Sort by newest / most viewed"),
+            ("Enter", "Read selected post"),
+            ("/", "Search posts"),
+            ("Esc", "Clear search results, or back to boards"),
+            ("b", "Back to boards"),
+            ("n", "New post"),
+            ("e", "Edit your post"),
+            ("r", "Refresh"),
+            ("w", "Who's online"),
+            ("t", "Switch colour theme"),
+            ("?", "Show this help"),
+            ("q", "Quit"),
+        ],
+    ),
+    (
+        "Reading",
+        &[("↑ / k, ↓ / j", "Scroll"), ("q / Esc", "Back to list")],
+    ),
+    (
+        "Writing",
+        &[
+            ("Enter", "Title → body"),
+            ("Ctrl-S", "Publish / save"),
+            ("Esc", "Discard"),
+        ],
+    ),
+];
+
+/// Makes text from other users safe to draw in someone's terminal.
+///
+/// Cells are written to the client verbatim, so an embedded ESC could move
+/// the cursor, clear the screen or retitle the window. C0 controls and DEL
+/// are shown caret-style (`^[`, `^?`) and C1 controls become U+FFFD;
+/// newlines and tabs are kept, and CRLF is folded to a newline.
+pub fn sanitize_for_terminal(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' | '\t' => out.push(c),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\x00'..='\x1f' => {
+                out.push('^');
+                out.push((c as u8 + b'@') as char);
+            }
+            '\x7f' => out.push_str("^?"),
+            '\u{80}'..='\u{9f}' => out.push(char::REPLACEMENT_CHARACTER),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Posts per page of the browsing list until the server sets `page_size`.
+const DEFAULT_PAGE_SIZE: usize = 25;
+
+/// Order of the browsing list. Pinned posts stay on top when newest-first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostSort {
+    Newest,
+    MostViewed,
+}
+
+impl PostSort {
+    pub fn toggled(self) -> Self {
+        match self {
+            PostSort::Newest => PostSort::MostViewed,
+            PostSort::MostViewed => PostSort::Newest,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PostSort::Newest => "newest",
+            PostSort::MostViewed => "most viewed",
+        }
+    }
+}
+
+/// Colours the TUI is drawn with, by role rather than by hue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Headings, borders and other chrome.
+    pub accent: Color,
+    /// Key hints, post numbers and the field being typed into.
+    pub key: Color,
+    /// Secondary text: previews, descriptions, inactive fields.
+    pub muted: Color,
+    /// Unread markers and success messages.
+    pub positive: Color,
+    /// Errors and the admin badge.
+    pub error: Color,
+    /// The security alert. Red in every theme so it never blends in.
+    pub alert: Color,
+    /// The guest badge.
+    pub badge: Color,
+    /// Footer text.
+    pub text: Color,
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+}
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme {
+        name: "default",
+        accent: Color::Cyan,
+        key: Color::Yellow,
+        muted: Color::Gray,
+        positive: Color::Green,
+        error: Color::Red,
+        alert: Color::Red,
+        badge: Color::Magenta,
+        text: Color::White,
+        selection_fg: Color::Cyan,
+        selection_bg: Color::Blue,
+    };
+
+    /// For terminals with a light background, where yellow and white vanish.
+    pub const LIGHT: Theme = Theme {
+        name: "light",
+        accent: Color::Blue,
+        key: Color::Magenta,
+        muted: Color::DarkGray,
+        positive: Color::Green,
+        error: Color::Red,
+        alert: Color::Red,
+        badge: Color::Magenta,
+        text: Color::Black,
+        selection_fg: Color::White,
+        selection_bg: Color::Blue,
+    };
+
+    pub const HIGH_CONTRAST: Theme = Theme {
+        name: "high-contrast",
+        accent: Color::White,
+        key: Color::LightYellow,
+        muted: Color::White,
+        positive: Color::LightGreen,
+        error: Color::LightRed,
+        alert: Color::Red,
+        badge: Color::LightMagenta,
+        text: Color::White,
+        selection_fg: Color::Black,
+        selection_bg: Color::White,
+    };
+
+    /// Built-in themes, in the order `t` cycles through them.
+    pub const ALL: [Theme; 3] = [Theme::DEFAULT, Theme::LIGHT, Theme::HIGH_CONTRAST];
+
+    pub fn next(self) -> Theme {
+        let idx = Theme::ALL
+            .iter()
+            .position(|theme| theme.name == self.name)
+            .unwrap_or(0);
+        Theme::ALL[(idx + 1) % Theme::ALL.len()]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DEFAULT
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -20,6 +212,12 @@ pub enum LoginStep {
     Password,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComposeStep {
+    Title,
+    Body,
+}
+
 pub struct App {
     pub state: AppState,
     pub login_step: LoginStep,
@@ -27,8 +225,49 @@ pub struct App {
     pub temp_username: Option<String>,
     pub login_error: Option<String>,
     pub alert_info: Option<(String, String)>,
+    /// Boards on the index, listed below the "All posts" entry.
+    pub boards: Vec<Board>,
+    /// Cursor on the board index; 0 is "All posts".
+    pub board_selected: usize,
+    /// Board whose posts are listed; `None` lists every board.
+    pub current_board: Option<Uuid>,
     pub posts: Vec<Post>,
     pub selected: usize,
+    /// Zero-based page of the browsing list; search results are not paged.
+    pub page: usize,
+    /// Posts per page of the browsing list.
+    pub page_size: usize,
+    /// Whether the list continues past the current page.
+    pub has_more_posts: bool,
+    pub sort: PostSort,
+    pub search_query: Option<String>,
+    pub editing_post_id: Option<Uuid>,
+    pub status_message: Option<String>,
+    pub is_guest: bool,
+    pub viewing_post: Option<Post>,
+    pub scroll: u16,
+    pub user_id: Option<Uuid>,
+    /// Signed-in username; `None` at the login screen and for guests.
+    pub username: Option<String>,
+    pub is_admin: bool,
+    /// When this client opened its session.
+    pub connected_at: DateTime<Utc>,
+    /// Last keypress, for the idle logout.
+    pub last_activity: Instant,
+    /// Snapshot shown by the "who's online" overlay.
+    pub online: Vec<OnlineUser>,
+    pub compose_step: ComposeStep,
+    pub compose_title: String,
+    /// Operator banner shown above the login prompt.
+    pub motd: Option<String>,
+    /// The signed-in user's previous visit; later posts are shown as new.
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Posts on every board that are new since `last_seen_at`.
+    pub unread_count: i64,
+    /// Colours for this session; `t` switches to the next built-in theme.
+    pub theme: Theme,
+    /// Trailing bytes of a UTF-8 sequence split across `data` calls.
+    pending_input: Vec<u8>,
 }
 
 impl App {
@@ -40,8 +279,34 @@ impl App {
             temp_username: None,
             login_error: None,
             alert_info: None,
+            boards: Vec::new(),
+            board_selected: 0,
+            current_board: None,
             posts: Vec::new(),
             selected: 0,
+            page: 0,
+            page_size: DEFAULT_PAGE_SIZE,
+            has_more_posts: false,
+            sort: PostSort::Newest,
+            search_query: None,
+            editing_post_id: None,
+            status_message: None,
+            is_guest: false,
+            viewing_post: None,
+            scroll: 0,
+            user_id: None,
+            username: None,
+            is_admin: false,
+            connected_at: Utc::now(),
+            last_activity: Instant::now(),
+            online: Vec::new(),
+            compose_step: ComposeStep::Title,
+            compose_title: String::new(),
+            motd: None,
+            last_seen_at: None,
+            unread_count: 0,
+            theme: Theme::default(),
+            pending_input: Vec::new(),
         }
     }
 
@@ -49,41 +314,258 @@ impl App {
         self.input_buffer.push(c);
     }
 
+    /// Appends a chunk of raw terminal input to `input_buffer`.
+    ///
+    /// Chunks may hold several characters (pastes, fast typing) or only part
+    /// of a multi-byte character, so bytes are decoded as UTF-8 with any
+    /// incomplete tail kept for the next call. Control characters are dropped,
+    /// except backspace and, when `multiline` is set, carriage return/newline.
+    pub fn push_input(&mut self, data: &[u8], multiline: bool) {
+        // Escape sequences (arrow keys etc.) are not text.
+        if data.first() == Some(&0x1b) {
+            self.pending_input.clear();
+            return;
+        }
+
+        self.pending_input.extend_from_slice(data);
+        let bytes = std::mem::take(&mut self.pending_input);
+
+        let mut rest = bytes.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    self.push_text(text, multiline);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    self.push_text(std::str::from_utf8(valid).unwrap_or_default(), multiline);
+                    match e.error_len() {
+                        Some(invalid) => rest = &after[invalid..],
+                        None => {
+                            self.pending_input = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str, multiline: bool) {
+        for c in text.chars() {
+            match c {
+                '\r' | '\n' if multiline => self.add_char('\n'),
+                '\x7f' | '\x08' => self.backspace(),
+                c if !c.is_control() => self.add_char(c),
+                _ => {}
+            }
+        }
+    }
+
+    /// Records a keypress.
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.elapsed()
+    }
+
     pub fn backspace(&mut self) {
         self.input_buffer.pop();
     }
 
+    /// Drops typed text, along with any half-received character, so nothing
+    /// carries over into the next field.
     pub fn clear_input(&mut self) {
         self.input_buffer.clear();
+        self.pending_input.clear();
     }
 
-    pub fn transition_to_browsing(&mut self) {
-        self.state = AppState::Browsing;
-        self.input_buffer.clear();
+    /// Switches screens. Input typed for the old screen never reaches the
+    /// new one; callers that prefill the buffer do so afterwards.
+    fn enter_state(&mut self, state: AppState) {
+        self.state = state;
+        self.clear_input();
+    }
+
+    /// Leaves the login screen for the board index.
+    pub fn transition_to_boards(&mut self) {
+        self.enter_state(AppState::BoardList);
         self.temp_username = None;
         self.login_error = None;
     }
 
+    pub fn enter_as_user(&mut self, user_id: Uuid, username: Option<String>, is_admin: bool) {
+        self.user_id = Some(user_id);
+        self.username = username;
+        self.is_admin = is_admin;
+        self.transition_to_boards();
+    }
+
+    pub fn enter_as_guest(&mut self) {
+        self.is_guest = true;
+        self.transition_to_boards();
+    }
+
     pub fn reset_login(&mut self, error: Option<String>) {
         self.login_step = LoginStep::Username;
-        self.input_buffer.clear();
+        self.clear_input();
         self.temp_username = None;
         self.login_error = error;
     }
 
     pub fn show_security_alert(&mut self, old_ip: String, new_ip: String) {
-        self.state = AppState::SecurityAlert;
+        self.enter_state(AppState::SecurityAlert);
         self.alert_info = Some((old_ip, new_ip));
-        self.input_buffer.clear();
         self.temp_username = None;
         self.login_error = None;
     }
 
+    /// Replaces the board index, keeping the cursor on the same board when
+    /// it is still there.
+    pub fn set_boards(&mut self, boards: Vec<Board>) {
+        let selected_id = self
+            .board_selected
+            .checked_sub(1)
+            .and_then(|idx| self.boards.get(idx).map(|board| board.id));
+        self.boards = boards;
+
+        let same_board = selected_id.and_then(|id| self.boards.iter().position(|b| b.id == id));
+        self.board_selected = match same_board {
+            Some(idx) => idx + 1,
+            None => self.board_selected.min(self.boards.len()),
+        };
+    }
+
+    pub fn next_board(&mut self) {
+        self.board_selected = (self.board_selected + 1) % (self.boards.len() + 1);
+    }
+
+    pub fn previous_board(&mut self) {
+        self.board_selected = match self.board_selected {
+            0 => self.boards.len(),
+            idx => idx - 1,
+        };
+    }
+
+    /// Lists the posts of the board under the cursor, from the first page.
+    /// The caller loads the list.
+    pub fn open_selected_board(&mut self) {
+        self.current_board = self
+            .board_selected
+            .checked_sub(1)
+            .and_then(|idx| self.boards.get(idx))
+            .map(|board| board.id);
+        self.enter_state(AppState::Browsing);
+        self.search_query = None;
+        self.status_message = None;
+        self.page = 0;
+        self.jump_to(0);
+    }
+
+    pub fn back_to_boards(&mut self) {
+        self.enter_state(AppState::BoardList);
+        self.search_query = None;
+        self.status_message = None;
+    }
+
+    /// Name of the board being browsed; `None` for all posts.
+    pub fn current_board_name(&self) -> Option<&str> {
+        let id = self.current_board?;
+        self.boards
+            .iter()
+            .find(|board| board.id == id)
+            .map(|board| board.name.as_str())
+    }
+
+    pub fn start_search(&mut self) {
+        self.enter_state(AppState::Searching);
+        self.status_message = None;
+    }
+
+    pub fn finish_search(&mut self, query: String) {
+        self.enter_state(AppState::Browsing);
+        self.search_query = Some(query);
+        self.selected = 0;
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.enter_state(AppState::Browsing);
+        self.search_query = None;
+        self.selected = 0;
+    }
+
+    pub fn start_editing(&mut self, post_id: Uuid, content: String) {
+        self.enter_state(AppState::Editing);
+        self.editing_post_id = Some(post_id);
+        self.input_buffer = content;
+        self.status_message = None;
+    }
+
+    pub fn stop_editing(&mut self, status: Option<String>) {
+        self.enter_state(AppState::Browsing);
+        self.editing_post_id = None;
+        self.status_message = status;
+    }
+
+    pub fn start_composing(&mut self) {
+        self.enter_state(AppState::Composing);
+        self.compose_step = ComposeStep::Title;
+        self.compose_title.clear();
+        self.status_message = None;
+    }
+
+    pub fn stop_composing(&mut self, status: Option<String>) {
+        self.enter_state(AppState::Browsing);
+        self.compose_step = ComposeStep::Title;
+        self.compose_title.clear();
+        self.status_message = status;
+    }
+
+    pub fn start_viewing(&mut self, post: Post) {
+        self.enter_state(AppState::Viewing);
+        self.viewing_post = Some(post);
+        self.scroll = 0;
+        self.status_message = None;
+    }
+
+    pub fn stop_viewing(&mut self) {
+        self.enter_state(AppState::Browsing);
+        self.viewing_post = None;
+        self.scroll = 0;
+    }
+
+    pub fn scroll_down(&mut self) {
+        let max = self
+            .viewing_post
+            .as_ref()
+            .map(|p| p.content.lines().count().saturating_sub(1))
+            .unwrap_or(0);
+        if (self.scroll as usize) < max {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    /// Replaces the list, keeping the cursor on the same post when it is
+    /// still present (a newly pinned or published post can shift it down).
+    /// Otherwise the cursor stays at its position, clamped to the new list,
+    /// and always lands on 0 for an empty list.
     pub fn set_posts(&mut self, posts: Vec<Post>) {
+        let selected_id = self.selected_post().map(|p| p.id);
         self.posts = posts;
-        if self.selected >= self.posts.len() && !self.posts.is_empty() {
-            self.selected = self.posts.len() - 1;
-        }
+
+        let same_post = selected_id.and_then(|id| self.posts.iter().position(|p| p.id == id));
+        self.selected = match same_post {
+            Some(idx) => idx,
+            None if self.posts.is_empty() => 0,
+            None => self.selected.min(self.posts.len() - 1),
+        };
     }
 
     pub fn next(&mut self) {
@@ -102,9 +584,75 @@ impl App {
         }
     }
 
+    pub fn at_last_post(&self) -> bool {
+        self.selected + 1 >= self.posts.len()
+    }
+
+    /// Moves to the following page, if any, with the cursor on its first
+    /// post. The caller reloads the list.
+    pub fn next_page(&mut self) -> bool {
+        if self.search_query.is_some() || !self.has_more_posts {
+            return false;
+        }
+        self.page += 1;
+        self.jump_to(0);
+        true
+    }
+
+    /// Moves to the preceding page, if any. `select_last` puts the cursor on
+    /// its last post, for scrolling up past the top of a page.
+    pub fn previous_page(&mut self, select_last: bool) -> bool {
+        if self.search_query.is_some() || self.page == 0 {
+            return false;
+        }
+        self.page -= 1;
+        self.jump_to(if select_last { self.page_size - 1 } else { 0 });
+        true
+    }
+
+    /// Switches the sort order and starts over from the first page.
+    pub fn toggle_sort(&mut self) {
+        self.sort = self.sort.toggled();
+        self.page = 0;
+        self.jump_to(0);
+    }
+
+    /// Drops the current list so the next `set_posts` places the cursor at
+    /// `index` instead of following the previously selected post.
+    fn jump_to(&mut self, index: usize) {
+        self.posts.clear();
+        self.selected = index;
+    }
+
     pub fn selected_post(&self) -> Option<&Post> {
         self.posts.get(self.selected)
     }
+
+    /// Guests have no history, so nothing is marked new for them.
+    pub fn is_unread(&self, post: &Post) -> bool {
+        self.user_id.is_some() && is_unread(post.created_at, self.last_seen_at)
+    }
+
+    pub fn cycle_theme(&mut self) {
+        self.theme = self.theme.next();
+        self.status_message = Some(format!("Theme: {}", self.theme.name));
+    }
+
+    pub fn show_help(&mut self) {
+        self.enter_state(AppState::Help);
+        self.status_message = None;
+    }
+
+    /// Closes the help or who's-online overlay.
+    pub fn hide_help(&mut self) {
+        self.enter_state(AppState::Browsing);
+    }
+
+    pub fn show_online(&mut self, users: Vec<OnlineUser>) {
+        self.online = users;
+        self.enter_state(AppState::Online);
+        self.status_message = None;
+    }
 }
 
 pub fn render(f: &mut Frame, app: &App) {
@@ -113,11 +661,154 @@ pub fn render(f: &mut Frame, app: &App) {
     match app.state {
         AppState::Login => render_login(f, app, area),
         AppState::SecurityAlert => render_security_alert(f, app, area),
-        AppState::Browsing => render_browsing(f, app, area),
+        AppState::BoardList => render_board_list(f, app, area),
+        AppState::Browsing | AppState::Searching => render_browsing(f, app, area),
+        AppState::Editing => render_editing(f, app, area),
+        AppState::Viewing => render_viewing(f, app, area),
+        AppState::Composing => render_composing(f, app, area),
+        AppState::Help => {
+            render_browsing(f, app, area);
+            render_help(f, app, area);
+        }
+        AppState::Online => {
+            render_browsing(f, app, area);
+            render_online(f, app, area);
+        }
+    }
+}
+
+/// Returns a `width` x `height` rect centered in `area`, clamped to fit.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(height.min(area.height)),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(width.min(area.width)),
+            Constraint::Min(0),
+        ])
+        .split(vertical[1])[1]
+}
+
+/// Draws the keybinding popup on top of whatever was rendered underneath.
+fn render_help(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let key_style = Style::default().fg(theme.key);
+    let heading_style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines = Vec::new();
+    for (idx, (section, keys)) in HELP_SECTIONS.iter().enumerate() {
+        if idx > 0 {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(*section, heading_style)));
+        for (key, action) in keys.iter() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<14}", key), key_style),
+                Span::raw(*action),
+            ]));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default().fg(theme.muted),
+    )));
+
+    let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let popup = centered_rect(width, height, area);
+
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title(" Keybindings "),
+    );
+
+    f.render_widget(Clear, popup);
+    f.render_widget(help, popup);
+}
+
+/// Draws the who's-online popup from the snapshot taken when it opened.
+fn render_online(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let now = Utc::now();
+    let mut lines: Vec<Line> = app
+        .online
+        .iter()
+        .map(|user| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {:<20}", sanitize_for_terminal(user.shown_name())),
+                    Style::default().fg(theme.key),
+                ),
+                Span::raw(format!("{:<5}", user.via)),
+                Span::styled(
+                    format!("{:>8}", user.connected_for(now)),
+                    Style::default().fg(theme.muted),
+                ),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from("  Nobody is online"));
     }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default().fg(theme.muted),
+    )));
+
+    let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let popup = centered_rect(width, height, area);
+
+    let online = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title(format!(" Who's online ({}) ", app.online.len())),
+    );
+
+    f.render_widget(Clear, popup);
+    f.render_widget(online, popup);
 }
 
 fn render_login(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let area = match &app.motd {
+        Some(motd) => {
+            let [banner, rest] = *Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(motd.lines().count() as u16 + 2),
+                    Constraint::Min(0),
+                ])
+                .margin(1)
+                .split(area)
+            else {
+                return;
+            };
+            let banner_text = Paragraph::new(motd.as_str())
+                .block(Block::default().borders(Borders::ALL))
+                .style(Style::default().fg(theme.positive));
+            f.render_widget(banner_text, banner);
+            rest
+        }
+        None => area,
+    };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -133,7 +824,7 @@ fn render_login(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         .block(Block::default().borders(Borders::ALL))
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         );
     f.render_widget(title, chunks[0]);
@@ -145,13 +836,13 @@ fn render_login(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
             } else {
                 format!("Username: {}_", app.input_buffer)
             };
-            (text, Style::default().fg(Color::Yellow))
+            (text, Style::default().fg(theme.key))
         }
         LoginStep::Password => {
             let username = app.temp_username.as_deref().unwrap_or("");
             (
                 format!("Username: {}", username),
-                Style::default().fg(Color::Gray),
+                Style::default().fg(theme.muted),
             )
         }
     };
@@ -162,15 +853,15 @@ fn render_login(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     f.render_widget(username_input, chunks[1]);
 
     let (password_text, password_style) = match app.login_step {
-        LoginStep::Username => ("Password: ".to_string(), Style::default().fg(Color::Gray)),
+        LoginStep::Username => ("Password: ".to_string(), Style::default().fg(theme.muted)),
         LoginStep::Password => {
-            let masked = "*".repeat(app.input_buffer.len());
+            let masked = "*".repeat(app.input_buffer.chars().count());
             let text = if app.input_buffer.is_empty() {
                 "Password: _".to_string()
             } else {
                 format!("Password: {}_", masked)
             };
-            (text, Style::default().fg(Color::Yellow))
+            (text, Style::default().fg(theme.key))
         }
     };
 
@@ -181,19 +872,20 @@ fn render_login(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
 
     if let Some(error) = &app.login_error {
         let error_msg = Paragraph::new(error.as_str())
-            .style(Style::default().fg(Color::Red))
+            .style(Style::default().fg(theme.error))
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(error_msg, chunks[3]);
     }
 }
 
 fn render_security_alert(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
     let default_ips = ("Unknown".to_string(), "Unknown".to_string());
     let (old_ip, new_ip) = app.alert_info.as_ref().unwrap_or(&default_ips);
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Red))
+        .border_style(Style::default().fg(theme.alert))
         .title(" SECURITY ALERT ");
 
     let chunks = Layout::default()
@@ -213,19 +905,21 @@ fn render_security_alert(f: &mut Frame, app: &App, area: ratatui::layout::Rect)
 
     let title_text = vec![Line::from(vec![Span::styled(
         "⚠️  SECURITY ALERT  ⚠️",
-        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        Style::default()
+            .fg(theme.alert)
+            .add_modifier(Modifier::BOLD),
     )])];
     let title = Paragraph::new(title_text).alignment(ratatui::layout::Alignment::Center);
     f.render_widget(title, chunks[0]);
 
     let message = Paragraph::new("Login detected from a different IP address!")
-        .style(Style::default().fg(Color::Yellow))
+        .style(Style::default().fg(theme.key))
         .alignment(ratatui::layout::Alignment::Center);
     f.render_widget(message, chunks[1]);
 
     let prev_ip_text = vec![Line::from(vec![
-        Span::styled("Previous IP: ", Style::default().fg(Color::Gray)),
-        Span::styled(old_ip, Style::default().fg(Color::Yellow)),
+        Span::styled("Previous IP: ", Style::default().fg(theme.muted)),
+        Span::styled(old_ip, Style::default().fg(theme.key)),
     ])];
     let prev_ip = Paragraph::new(prev_ip_text)
         .block(Block::default().borders(Borders::ALL))
@@ -233,8 +927,8 @@ fn render_security_alert(f: &mut Frame, app: &App, area: ratatui::layout::Rect)
     f.render_widget(prev_ip, chunks[2]);
 
     let curr_ip_text = vec![Line::from(vec![
-        Span::styled("Current IP:  ", Style::default().fg(Color::Gray)),
-        Span::styled(new_ip, Style::default().fg(Color::Green)),
+        Span::styled("Current IP:  ", Style::default().fg(theme.muted)),
+        Span::styled(new_ip, Style::default().fg(theme.positive)),
     ])];
     let curr_ip = Paragraph::new(curr_ip_text)
         .block(Block::default().borders(Borders::ALL))
@@ -242,84 +936,836 @@ fn render_security_alert(f: &mut Frame, app: &App, area: ratatui::layout::Rect)
     f.render_widget(curr_ip, chunks[3]);
 
     let instruction = Paragraph::new("Press [Enter] to acknowledge and continue")
-        .style(Style::default().fg(Color::Cyan))
+        .style(Style::default().fg(theme.accent))
         .alignment(ratatui::layout::Alignment::Center);
     f.render_widget(instruction, chunks[4]);
 }
 
-fn render_browsing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    if app.posts.is_empty() {
-        let paragraph = Paragraph::new("No posts available.\nPress 'q' to quit.")
-            .block(Block::default().borders(Borders::ALL).title("Iron BBS"))
-            .style(Style::default().fg(Color::Gray));
-        f.render_widget(paragraph, area);
-        return;
-    }
-
+fn render_board_list(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(0), Constraint::Length(3)])
         .split(area);
 
-    let items: Vec<ListItem> = app
-        .posts
-        .iter()
-        .enumerate()
-        .map(|(idx, post)| {
-            let is_selected = idx == app.selected;
-            let style = if is_selected {
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-            };
-
-            let content = vec![
-                Line::from(vec![
-                    Span::styled(format!("{}. ", idx + 1), Style::default().fg(Color::Yellow)),
-                    Span::styled(&post.title, style),
-                ]),
+    let all_posts = ListItem::new(vec![
+        Line::from(Span::styled(
+            "All posts",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "   Everything, from every board",
+            Style::default().fg(theme.muted),
+        )),
+    ]);
+    let items: Vec<ListItem> = std::iter::once(all_posts)
+        .chain(app.boards.iter().map(|board| {
+            let description = board.description.as_deref().unwrap_or_default();
+            ListItem::new(vec![
                 Line::from(Span::styled(
-                    format!("   {}", post.preview(60)),
-                    Style::default().fg(Color::Gray),
+                    sanitize_for_terminal(&board.name),
+                    Style::default().add_modifier(Modifier::BOLD),
                 )),
-            ];
-            ListItem::new(content)
-        })
+                Line::from(Span::styled(
+                    format!("   {}", sanitize_for_terminal(description)),
+                    Style::default().fg(theme.muted),
+                )),
+            ])
+        }))
         .collect();
 
     let mut list_state = ListState::default();
-    list_state.select(Some(app.selected));
+    list_state.select(Some(app.board_selected));
 
     let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Iron BBS - Posts (Interactive TUI)"),
+                .title("Iron BBS - Boards"),
         )
         .highlight_style(
             Style::default()
-                .bg(Color::Blue)
+                .fg(theme.selection_fg)
+                .bg(theme.selection_bg)
                 .add_modifier(Modifier::BOLD),
         );
-
     f.render_stateful_widget(list, chunks[0], &mut list_state);
 
-    let footer_text = vec![Line::from(vec![
-        Span::styled("↑/k", Style::default().fg(Color::Yellow)),
+    let mut spans = vec![
+        Span::styled("↑/k", Style::default().fg(theme.key)),
         Span::raw(" up | "),
-        Span::styled("↓/j", Style::default().fg(Color::Yellow)),
+        Span::styled("↓/j", Style::default().fg(theme.key)),
         Span::raw(" down | "),
-        Span::styled("Enter", Style::default().fg(Color::Yellow)),
-        Span::raw(" view | "),
-        Span::styled("q", Style::default().fg(Color::Yellow)),
+        Span::styled("Enter", Style::default().fg(theme.key)),
+        Span::raw(" open | "),
+        Span::styled("r", Style::default().fg(theme.key)),
+        Span::raw(" refresh | "),
+        Span::styled("q", Style::default().fg(theme.key)),
         Span::raw(" quit"),
+    ];
+    if app.is_guest {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            "Guest (read-only)",
+            Style::default().fg(theme.badge),
+        ));
+    }
+    let footer = Paragraph::new(Line::from(spans))
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(theme.text));
+    f.render_widget(footer, chunks[1]);
+}
+
+fn render_browsing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let searching = app.state == AppState::Searching;
+
+    if app.posts.is_empty() && app.search_query.is_none() && !searching {
+        let paragraph = Paragraph::new("No posts available.\nPress 'b' for boards or 'q' to quit.")
+            .block(Block::default().borders(Borders::ALL).title("Iron BBS"))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let list_title = match (&app.search_query, app.current_board_name()) {
+        (Some(query), _) => format!("{} results for '{}'", app.posts.len(), query),
+        (None, Some(board)) => format!("Iron BBS - {}", sanitize_for_terminal(board)),
+        (None, None) => "Iron BBS - Posts (Interactive TUI)".to_string(),
+    };
+
+    if app.posts.is_empty() {
+        let paragraph = Paragraph::new("No matching posts.\nPress Esc to return to all posts.")
+            .block(Block::default().borders(Borders::ALL).title(list_title))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(paragraph, chunks[0]);
+    } else {
+        let items: Vec<ListItem> = app
+            .posts
+            .iter()
+            .enumerate()
+            .map(|(idx, post)| {
+                let is_selected = idx == app.selected;
+                let is_unread = app.is_unread(post);
+                let style = if is_selected {
+                    Style::default()
+                        .fg(theme.selection_fg)
+                        .add_modifier(Modifier::BOLD)
+                } else if is_unread {
+                    Style::default()
+                        .fg(theme.positive)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+
+                let number = match app.search_query {
+                    Some(_) => idx + 1,
+                    None => app.page * app.page_size + idx + 1,
+                };
+                let mut title = vec![Span::styled(
+                    format!("{}. ", number),
+                    Style::default().fg(theme.key),
+                )];
+                if is_unread {
+                    title.push(Span::styled("● ", Style::default().fg(theme.positive)));
+                }
+                if post.pinned {
+                    title.push(Span::raw("📌 "));
+                }
+                title.push(Span::styled(sanitize_for_terminal(&post.title), style));
+
+                let content = vec![
+                    Line::from(title),
+                    Line::from(Span::styled(
+                        format!("   {}", sanitize_for_terminal(&post.preview(60))),
+                        Style::default().fg(theme.muted),
+                    )),
+                ];
+                ListItem::new(content)
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        list_state.select(Some(app.selected));
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(list_title))
+            .highlight_style(
+                Style::default()
+                    .bg(theme.selection_bg)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+        f.render_stateful_widget(list, chunks[0], &mut list_state);
+    }
+
+    let footer_spans = if searching {
+        vec![
+            Span::styled("Search: ", Style::default().fg(theme.key)),
+            Span::raw(format!("{}_", app.input_buffer)),
+            Span::raw("  ("),
+            Span::styled("Enter", Style::default().fg(theme.key)),
+            Span::raw(" search | "),
+            Span::styled("Esc", Style::default().fg(theme.key)),
+            Span::raw(" cancel)"),
+        ]
+    } else {
+        let mut spans = vec![
+            Span::styled("↑/k", Style::default().fg(theme.key)),
+            Span::raw(" up | "),
+            Span::styled("↓/j", Style::default().fg(theme.key)),
+            Span::raw(" down | "),
+            Span::styled("Enter", Style::default().fg(theme.key)),
+            Span::raw(" view | "),
+            Span::styled("/", Style::default().fg(theme.key)),
+            Span::raw(" search | "),
+            Span::styled("n", Style::default().fg(theme.key)),
+            Span::raw(" new | "),
+            Span::styled("e", Style::default().fg(theme.key)),
+            Span::raw(" edit | "),
+            Span::styled("b", Style::default().fg(theme.key)),
+            Span::raw(" boards | "),
+            Span::styled("?", Style::default().fg(theme.key)),
+            Span::raw(" help | "),
+            Span::styled("q", Style::default().fg(theme.key)),
+            Span::raw(" quit"),
+        ];
+        if app.search_query.is_none() {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                format!("Page {} · {}", app.page + 1, app.sort.label()),
+                Style::default().fg(theme.accent),
+            ));
+        }
+        if app.user_id.is_some() {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                format!("{} new", app.unread_count),
+                Style::default().fg(theme.positive),
+            ));
+        }
+        if app.is_admin {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                "[admin]",
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        if app.is_guest {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                "Guest (read-only)",
+                Style::default().fg(theme.badge),
+            ));
+        }
+        if let Some(status) = &app.status_message {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                status.as_str(),
+                Style::default().fg(theme.positive),
+            ));
+        }
+        spans
+    };
+    let footer_text = vec![Line::from(footer_spans)];
+
+    let footer = Paragraph::new(footer_text)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(footer, chunks[1]);
+}
+
+fn render_editing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let title = app
+        .posts
+        .iter()
+        .find(|p| Some(p.id) == app.editing_post_id)
+        .map(|p| format!("Editing: {}", sanitize_for_terminal(&p.title)))
+        .unwrap_or_else(|| "Editing".to_string());
+
+    let editor = Paragraph::new(format!("{}_", sanitize_for_terminal(&app.input_buffer)))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false });
+    f.render_widget(editor, chunks[0]);
+
+    let footer_text = vec![Line::from(vec![
+        Span::styled("Ctrl-S", Style::default().fg(theme.key)),
+        Span::raw(" save | "),
+        Span::styled("Esc", Style::default().fg(theme.key)),
+        Span::raw(" cancel"),
+    ])];
+
+    let footer = Paragraph::new(footer_text)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(footer, chunks[1]);
+}
+
+fn render_viewing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let Some(post) = &app.viewing_post else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let edited = if post.was_edited() { " (edited)" } else { "" };
+    let title = format!(
+        "{} ({} views){}",
+        sanitize_for_terminal(&post.title),
+        post.view_count,
+        edited
+    );
+    let body = Paragraph::new(sanitize_for_terminal(&post.content))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false })
+        .scroll((app.scroll, 0));
+    f.render_widget(body, chunks[0]);
+
+    let footer_text = vec![Line::from(vec![
+        Span::styled("↑/k", Style::default().fg(theme.key)),
+        Span::raw(" scroll up | "),
+        Span::styled("↓/j", Style::default().fg(theme.key)),
+        Span::raw(" scroll down | "),
+        Span::styled("q/Esc", Style::default().fg(theme.key)),
+        Span::raw(" back"),
     ])];
 
     let footer = Paragraph::new(footer_text)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(theme.text));
 
     f.render_widget(footer, chunks[1]);
 }
+
+fn render_composing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    let (title_text, title_style, body_text, body_style) = match app.compose_step {
+        ComposeStep::Title => (
+            format!("{}_", app.input_buffer),
+            Style::default().fg(theme.key),
+            String::new(),
+            Style::default().fg(theme.muted),
+        ),
+        ComposeStep::Body => (
+            app.compose_title.clone(),
+            Style::default().fg(theme.muted),
+            format!("{}_", app.input_buffer),
+            Style::default().fg(theme.key),
+        ),
+    };
+
+    let title = Paragraph::new(title_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("New post: title"),
+        )
+        .style(title_style);
+    f.render_widget(title, chunks[0]);
+
+    let body = Paragraph::new(body_text)
+        .block(Block::default().borders(Borders::ALL).title("Body"))
+        .style(body_style)
+        .wrap(Wrap { trim: false });
+    f.render_widget(body, chunks[1]);
+
+    let mut footer_spans = match app.compose_step {
+        ComposeStep::Title => vec![
+            Span::styled("Enter", Style::default().fg(theme.key)),
+            Span::raw(" next | "),
+            Span::styled("Esc", Style::default().fg(theme.key)),
+            Span::raw(" cancel"),
+        ],
+        ComposeStep::Body => vec![
+            Span::styled("Ctrl-S", Style::default().fg(theme.key)),
+            Span::raw(" publish | "),
+            Span::styled("Esc", Style::default().fg(theme.key)),
+            Span::raw(" cancel"),
+        ],
+    };
+    if let Some(status) = &app.status_message {
+        footer_spans.push(Span::raw("  "));
+        footer_spans.push(Span::styled(
+            status.as_str(),
+            Style::default().fg(theme.error),
+        ));
+    }
+
+    let footer = Paragraph::new(vec![Line::from(footer_spans)])
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(footer, chunks[2]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn screen(app: &App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| render(f, app)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_help_overlays_post_list() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        app.set_posts(vec![Post {
+            id: Uuid::new_v4(),
+            title: "First post".to_string(),
+            content: "Hello".to_string(),
+            author_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            published: true,
+            board_id: None,
+            parent_id: None,
+            deleted_at: None,
+            view_count: 0,
+            pinned: false,
+            slug: None,
+        }]);
+
+        app.show_help();
+        let text = screen(&app, 100, 40);
+
+        assert!(text.contains("Keybindings"));
+        assert!(text.contains("Search posts"));
+        assert!(text.contains("First post"));
+
+        app.hide_help();
+        assert_eq!(app.state, AppState::Browsing);
+        assert!(!screen(&app, 100, 40).contains("Keybindings"));
+    }
+
+    #[test]
+    fn test_themes_cycle_and_keep_the_alert_red() {
+        let mut app = App::new();
+        app.show_security_alert("10.0.0.1".to_string(), "10.0.0.2".to_string());
+
+        let mut seen = Vec::new();
+        for _ in 0..Theme::ALL.len() {
+            seen.push(app.theme.name);
+
+            let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+            terminal.draw(|f| render(f, &app)).unwrap();
+            let buffer = terminal.backend().buffer();
+            // Top-left border corner of the alert box.
+            assert_eq!(buffer.get(0, 0).fg, Color::Red, "{}", app.theme.name);
+
+            app.cycle_theme();
+        }
+
+        assert_eq!(seen, ["default", "light", "high-contrast"]);
+        assert_eq!(app.theme, Theme::DEFAULT);
+    }
+
+    fn titled_post(title: &str, pinned: bool) -> Post {
+        Post {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            content: String::new(),
+            author_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            published: true,
+            board_id: None,
+            parent_id: None,
+            deleted_at: None,
+            view_count: 0,
+            pinned,
+            slug: None,
+        }
+    }
+
+    fn board(name: &str) -> Board {
+        Board {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            slug: name.to_lowercase(),
+            description: Some(format!("All about {}", name)),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            visibility: "public".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_board_index_comes_first_and_opens_boards() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        assert_eq!(app.state, AppState::BoardList);
+
+        let general = board("General");
+        let rust = board("Rust");
+        app.set_boards(vec![general.clone(), rust.clone()]);
+        let text = screen(&app, 80, 20);
+        assert!(text.contains("All posts"));
+        assert!(text.contains("All about Rust"));
+        assert!(text.contains("Guest (read-only)"));
+
+        // "All posts" lists every board.
+        app.open_selected_board();
+        assert_eq!(app.state, AppState::Browsing);
+        assert_eq!(app.current_board, None);
+
+        app.back_to_boards();
+        app.previous_board();
+        assert_eq!(app.board_selected, 2);
+        app.set_posts(vec![titled_post("Stale", false)]);
+        app.page = 2;
+        app.open_selected_board();
+        assert_eq!(app.current_board, Some(rust.id));
+        assert_eq!(app.current_board_name(), Some("Rust"));
+        assert_eq!(app.page, 0);
+        assert!(app.posts.is_empty());
+        assert!(screen(&app, 80, 20).contains("No posts available"));
+
+        app.set_posts(vec![titled_post("Borrowing", false)]);
+        assert!(screen(&app, 80, 20).contains("Iron BBS - Rust"));
+
+        // Reloading the index follows the board under the cursor.
+        app.back_to_boards();
+        app.set_boards(vec![rust.clone()]);
+        assert_eq!(app.board_selected, 1);
+        app.set_boards(Vec::new());
+        assert_eq!(app.board_selected, 0);
+        app.next_board();
+        assert_eq!(app.board_selected, 0);
+    }
+
+    #[test]
+    fn test_set_posts_keeps_cursor_on_selected_post() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        let first = titled_post("First", false);
+        let second = titled_post("Second", false);
+        app.set_posts(vec![first.clone(), second.clone()]);
+        app.next();
+
+        let pinned = titled_post("Announcement", true);
+        app.set_posts(vec![pinned, first.clone(), second.clone()]);
+        assert_eq!(app.selected_post().unwrap().id, second.id);
+
+        app.set_posts(vec![first.clone()]);
+        assert_eq!(app.selected_post().unwrap().id, first.id);
+
+        app.set_posts(Vec::new());
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn test_browsing_marks_posts_new_since_last_visit() {
+        let mut older = titled_post("Old news", false);
+        older.created_at = Utc::now() - chrono::Duration::days(2);
+        let newer = titled_post("Fresh post", false);
+
+        let mut app = App::new();
+        app.enter_as_user(Uuid::new_v4(), Some("alice".to_string()), false);
+        app.open_selected_board();
+        app.last_seen_at = Some(Utc::now() - chrono::Duration::days(1));
+        app.set_posts(vec![newer.clone(), older.clone()]);
+        assert!(app.is_unread(&newer));
+        assert!(!app.is_unread(&older));
+        let text = screen(&app, 80, 20);
+        assert!(text.contains("● Fresh post"));
+        assert!(!text.contains("● Old news"));
+
+        // First visit: everything is new.
+        app.last_seen_at = None;
+        assert!(app.is_unread(&older));
+
+        let mut guest = App::new();
+        guest.enter_as_guest();
+        guest.open_selected_board();
+        guest.set_posts(vec![newer.clone()]);
+        assert!(!guest.is_unread(&newer));
+        assert!(!screen(&guest, 80, 20).contains('●'));
+    }
+
+    #[test]
+    fn test_paging_places_cursor_on_new_page() {
+        let mut app = App::new();
+        app.page_size = 10;
+        let page: Vec<Post> = (0..app.page_size)
+            .map(|_| titled_post("Post", false))
+            .collect();
+        app.set_posts(page.clone());
+        app.selected = app.page_size - 1;
+
+        assert!(!app.next_page());
+        app.has_more_posts = true;
+        assert!(app.next_page());
+        assert_eq!(app.page, 1);
+        app.set_posts(vec![titled_post("Post", false), titled_post("Post", false)]);
+        assert_eq!(app.selected, 0);
+
+        // Scrolling up past the top lands on the last post of the page before.
+        assert!(app.previous_page(true));
+        assert_eq!(app.page, 0);
+        app.set_posts(page.clone());
+        assert_eq!(app.selected, app.page_size - 1);
+        assert!(!app.previous_page(true));
+
+        app.page = 3;
+        app.toggle_sort();
+        assert_eq!(app.sort, PostSort::MostViewed);
+        assert_eq!(app.page, 0);
+        app.set_posts(page);
+        assert_eq!(app.selected, 0);
+
+        app.search_query = Some("rust".to_string());
+        assert!(!app.next_page());
+    }
+
+    #[test]
+    fn test_set_posts_from_empty_starts_at_top() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        app.set_posts(Vec::new());
+        app.next();
+        app.previous();
+        assert_eq!(app.selected, 0);
+        assert!(app.selected_post().is_none());
+
+        let first = titled_post("First", false);
+        app.set_posts(vec![first.clone(), titled_post("Second", false)]);
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.selected_post().unwrap().id, first.id);
+    }
+
+    #[test]
+    fn test_set_posts_clamps_when_selected_post_is_gone() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        let posts: Vec<Post> = (0..5)
+            .map(|i| titled_post(&format!("Post {}", i), false))
+            .collect();
+        app.set_posts(posts.clone());
+        for _ in 0..4 {
+            app.next();
+        }
+        assert_eq!(app.selected, 4);
+
+        // The selected post was removed and the list shrank past the cursor.
+        app.set_posts(posts[..2].to_vec());
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.selected_post().unwrap().id, posts[1].id);
+
+        // A post vanishing elsewhere in a long enough list leaves the index alone.
+        app.set_posts(vec![posts[3].clone(), posts[4].clone(), posts[2].clone()]);
+        assert_eq!(app.selected, 1);
+
+        app.set_posts(Vec::new());
+        assert_eq!(app.selected, 0);
+        app.next();
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn test_set_posts_follows_selected_post_when_reordered() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        let posts: Vec<Post> = (0..3)
+            .map(|i| titled_post(&format!("Post {}", i), false))
+            .collect();
+        app.set_posts(posts.clone());
+        app.next();
+        assert_eq!(app.selected_post().unwrap().id, posts[1].id);
+
+        let reordered = vec![posts[2].clone(), posts[0].clone(), posts[1].clone()];
+        app.set_posts(reordered);
+        assert_eq!(app.selected, 2);
+        assert_eq!(app.selected_post().unwrap().id, posts[1].id);
+
+        let trimmed = vec![posts[1].clone()];
+        app.set_posts(trimmed);
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.selected_post().unwrap().id, posts[1].id);
+    }
+
+    #[test]
+    fn test_login_shows_motd_above_title() {
+        let mut app = App::new();
+        assert!(screen(&app, 80, 30).contains("Welcome to Iron BBS"));
+
+        app.motd = Some("*** The Iron Board ***\nEst. 2024".to_string());
+        let text = screen(&app, 80, 30);
+
+        let banner = text.find("The Iron Board").unwrap();
+        let title = text.find("Welcome to Iron BBS").unwrap();
+        assert!(banner < title);
+        assert!(text.contains("Est. 2024"));
+    }
+
+    #[test]
+    fn test_viewer_marks_edited_posts() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        let mut post = titled_post("Changed later", false);
+        post.updated_at = post.created_at + chrono::Duration::hours(2);
+        app.start_viewing(post);
+
+        assert!(screen(&app, 100, 20).contains("(edited)"));
+
+        app.start_viewing(titled_post("Untouched", false));
+
+        assert!(!screen(&app, 100, 20).contains("(edited)"));
+    }
+
+    #[test]
+    fn test_pinned_posts_are_marked() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        app.set_posts(vec![
+            titled_post("Rules", true),
+            titled_post("Chatter", false),
+        ]);
+
+        let text = screen(&app, 100, 40);
+
+        assert!(text.contains("📌"));
+        assert!(text.contains("Chatter"));
+    }
+
+    #[test]
+    fn test_centered_rect_fits_small_areas() {
+        let area = Rect::new(0, 0, 20, 5);
+
+        let popup = centered_rect(40, 30, area);
+
+        assert_eq!(popup, area);
+    }
+
+    #[test]
+    fn test_push_input_accepts_multi_character_chunks() {
+        let mut app = App::new();
+
+        app.push_input(b"hunter2 pasted", false);
+
+        assert_eq!(app.input_buffer, "hunter2 pasted");
+    }
+
+    #[test]
+    fn test_sanitize_for_terminal_neutralizes_escape_sequences() {
+        assert_eq!(
+            sanitize_for_terminal("clear\x1b[2J\x1b[H me"),
+            "clear^[[2J^[[H me"
+        );
+        assert_eq!(
+            sanitize_for_terminal("\x1b]0;pwned\x07title"),
+            "^[]0;pwned^Gtitle"
+        );
+        assert_eq!(
+            sanitize_for_terminal("8-bit \u{9b}31m csi"),
+            "8-bit \u{fffd}31m csi"
+        );
+        assert_eq!(sanitize_for_terminal("del\x7f nul\0"), "del^? nul^@");
+        assert_eq!(
+            sanitize_for_terminal("line one\r\nline\ttwo\rover"),
+            "line one\nline\ttwo^Mover"
+        );
+        assert_eq!(sanitize_for_terminal("héllo 日本 📌"), "héllo 日本 📌");
+    }
+
+    #[test]
+    fn test_viewing_never_draws_control_characters() {
+        let mut app = App::new();
+        app.enter_as_guest();
+        app.open_selected_board();
+        let mut post = titled_post("Evil\x1b]0;owned\x07", false);
+        post.content = "before\x1b[2J\x1b[1;1Hafter".to_string();
+        app.set_posts(vec![post.clone()]);
+        app.start_viewing(post);
+
+        let text = screen(&app, 60, 10);
+        assert!(!text.chars().any(|c| c.is_control() && c != '\n'));
+        assert!(text.contains("before^[[2J^[[1;1Hafter"));
+        assert!(text.contains("Evil^[]0;owned^G"));
+
+        app.stop_viewing();
+        let text = screen(&app, 60, 10);
+        assert!(!text.chars().any(|c| c.is_control() && c != '\n'));
+    }
+
+    #[test]
+    fn test_push_input_buffers_split_utf8_sequences() {
+        let mut app = App::new();
+        let bytes = "héllo 日本".as_bytes();
+
+        app.push_input(&bytes[..2], false);
+        app.push_input(&bytes[2..9], false);
+        app.push_input(&bytes[9..], false);
+
+        assert_eq!(app.input_buffer, "héllo 日本");
+    }
+
+    #[test]
+    fn test_push_input_handles_newlines_and_backspace() {
+        let mut app = App::new();
+
+        app.push_input(b"ab\x7fc\rd", true);
+        assert_eq!(app.input_buffer, "ac\nd");
+
+        app.clear_input();
+        app.push_input(b"ab\rc", false);
+        assert_eq!(app.input_buffer, "abc");
+    }
+
+    #[test]
+    fn test_push_input_ignores_escape_sequences() {
+        let mut app = App::new();
+
+        app.push_input(b"x", false);
+        app.push_input(b"\x1b[A", false);
+
+        assert_eq!(app.input_buffer, "x");
+    }
+}
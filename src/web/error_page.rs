@@ -0,0 +1,92 @@
+use askama::Template;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use tower_cookies::Cookies;
+
+use super::{handlers::check_auth, AppState};
+use crate::csrf::ensure_csrf_token;
+use crate::error::ErrorMessage;
+
+#[derive(Template)]
+#[template(path = "error.html")]
+struct ErrorTemplate {
+    status: u16,
+    title: String,
+    message: String,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+/// Browsers ask for `text/html`; API clients and `/api/*` keep JSON errors.
+fn wants_html(request: &Request) -> bool {
+    !request.uri().path().starts_with("/api/")
+        && request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(|accept| accept.contains("text/html"))
+}
+
+/// Renders `Error` responses as a page for browsers. The error itself was
+/// already logged when it was turned into a response.
+///
+/// The page keeps the usual header, so a signed-in reader still sees their
+/// name and a working sign-out form.
+pub async fn html_errors(
+    State(state): State<Arc<AppState>>,
+    cookies: Cookies,
+    request: Request,
+    next: Next,
+) -> Response {
+    let html = wants_html(&request);
+    let response = next.run(request).await;
+    if !html {
+        return response;
+    }
+    let Some(ErrorMessage(message)) = response.extensions().get::<ErrorMessage>().cloned() else {
+        return response;
+    };
+
+    let status = response.status();
+    // Server-side details mean nothing to a reader, so they get an apology.
+    let message = if status.is_server_error() {
+        "Something went wrong on our side. Please try again in a moment.".to_string()
+    } else {
+        message
+    };
+    let template = ErrorTemplate {
+        status: status.as_u16(),
+        title: status.canonical_reason().unwrap_or("Error").to_string(),
+        message,
+        current_user: check_auth(&cookies, &state).await.map(|user| user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    match template.render() {
+        Ok(page) => {
+            // Only the body changes; status and headers such as Set-Cookie
+            // stay as the handler left them.
+            let (mut parts, _) = response.into_parts();
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            Response::from_parts(parts, Body::from(page))
+        }
+        Err(e) => {
+            tracing::error!("Error page failed to render: {}", e);
+            response
+        }
+    }
+}
+
+/// Requests that match no route.
+pub async fn not_found() -> crate::Error {
+    crate::Error::NotFound
+}
@@ -0,0 +1,44 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path},
+    http::request::Parts,
+};
+use uuid::Uuid;
+
+use crate::Error;
+
+/// A `:id` path segment parsed as a UUID.
+///
+/// A malformed id can't name any row, so it is reported as `Error::NotFound`
+/// with our JSON body instead of axum's plain-text 400.
+#[derive(Debug, Clone, Copy)]
+pub struct IdPath(pub Uuid);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IdPath {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(id) = Path::<Uuid>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| Error::NotFound)?;
+        Ok(Self(id))
+    }
+}
+
+/// `:id/history/:rev` path segments: a post id and one of its revision
+/// numbers. Malformed segments are `Error::NotFound`, as with `IdPath`.
+#[derive(Debug, Clone, Copy)]
+pub struct RevisionPath(pub Uuid, pub i32);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RevisionPath {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path((id, rev)) = Path::<(Uuid, i32)>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| Error::NotFound)?;
+        Ok(Self(id, rev))
+    }
+}
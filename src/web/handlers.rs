@@ -1,533 +1,3513 @@
 use askama::Template;
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Path, Query, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{Html, IntoResponse, Redirect, Response},
     Form, Json,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
-use tower_cookies::{Cookie, Cookies};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tower_cookies::{cookie::SameSite, Cookie, Cookies};
 use uuid::Uuid;
 
 use crate::{
-    auth::AuthService,
-    models::{Board, Post, PostWithAuthor, User},
+    auth::{parse_openssh_public_key, validate_password_strength, AuthEventKind, AuthService},
+    avatar::{identicon_svg, AvatarProvider, PLACEHOLDER_SVG},
+    csrf::ensure_csrf_token,
+    db, etag, feed,
+    maintenance::READ_ONLY_MESSAGE,
+    markdown,
+    models::{
+        diff_lines, is_unread, normalize_bio, normalize_display_name, parse_report_reason,
+        parse_tags, validate_board_name, validate_board_slug, validate_post_content,
+        validate_post_title, AccountExport, AuthEvent, AuthorizedKey, Board, BoardVisibility,
+        DiffLine, OnlineUser, Post, PostCounts, PostRevision, PostWithAuthor, ReportWithPost,
+        SessionInfo, User,
+    },
+    pow::{verify_pow, CHALLENGE_TTL},
+    presence::WEB_ONLINE_WINDOW_MINUTES,
+    ratelimit::{cooldown_message, lockout_message},
     Error, Result,
 };
 
-use super::{AppState, AuthPayload, CreatePostPayload, RegisterPayload};
+use super::extract::{IdPath, RevisionPath};
+use super::{
+    AccountSettingsPayload, AddKeyPayload, AppState, AuthPayload, ChangePasswordPayload,
+    CreatePostPayload, DeleteAccountPayload, EditPostPayload, ForgotPasswordPayload,
+    MaintenancePayload, PageQuery, PostQuery, RegisterPayload, ReplyPayload, ReportPayload,
+    ResetPasswordPayload, SearchQuery, VotePayload,
+};
+
+/// Sessions used more recently than this keep their expiry, so most
+/// requests only read the session row instead of rewriting it.
+const SESSION_TOUCH_INTERVAL_MINUTES: i64 = 5;
 
-async fn check_auth(cookies: &Cookies, db: &sqlx::PgPool) -> Option<User> {
+pub(super) async fn check_auth(cookies: &Cookies, state: &AppState) -> Option<User> {
     let session_cookie = cookies.get("session_id")?;
     let token = session_cookie.value();
 
     let session = sqlx::query!(
-        "SELECT user_id FROM sessions WHERE token = $1 AND expires_at > NOW()",
+        "SELECT user_id, created_at, last_active_at, remember FROM sessions WHERE token = $1 AND expires_at > NOW()",
         token
     )
-    .fetch_optional(db)
+    .fetch_optional(&state.db)
     .await
     .ok()??;
 
+    let now = Utc::now();
+    if now - session.last_active_at > Duration::minutes(SESSION_TOUCH_INTERVAL_MINUTES) {
+        let expires_at = state
+            .sessions
+            .expires_at(session.created_at, now, session.remember);
+        if let Err(e) = sqlx::query!(
+            "UPDATE sessions SET last_active_at = $2, expires_at = $3 WHERE token = $1",
+            token,
+            now,
+            expires_at
+        )
+        .execute(&state.db)
+        .await
+        {
+            tracing::warn!("Failed to extend session: {}", e);
+        }
+    }
+
     let user = sqlx::query_as!(
         User,
-        "SELECT id, username, email, password_hash, created_at, last_login_ip, last_login_at FROM users WHERE id = $1",
+        "SELECT id, username, email, password_hash, created_at, last_login_ip, last_login_at, verified_at, is_admin, display_name, bio FROM users WHERE id = $1",
         session.user_id
     )
-    .fetch_optional(db)
+    .fetch_optional(&state.db)
     .await
     .ok()??;
 
     Some(user)
 }
 
+/// Builds the `session_id` cookie. Remembered sessions get a `Max-Age` of
+/// the absolute session lifetime; others are browser-session cookies.
+fn session_cookie(token: String, max_age: Option<Duration>) -> Cookie<'static> {
+    let mut cookie = Cookie::new("session_id", token);
+    cookie.set_path("/");
+    cookie.set_http_only(true);
+    cookie.set_secure(true);
+    cookie.set_same_site(SameSite::Lax);
+    if let Some(max_age) = max_age {
+        cookie.set_max_age(tower_cookies::cookie::time::Duration::seconds(
+            max_age.num_seconds(),
+        ));
+    }
+    cookie
+}
+
+/// Creates a session row for `user_id` and sets the matching cookie.
+async fn start_session(
+    state: &AppState,
+    cookies: &Cookies,
+    user_id: Uuid,
+    remember: bool,
+) -> Result<()> {
+    let token = AuthService::generate_session_token();
+    let now = Utc::now();
+    let expires_at = state.sessions.expires_at(now, now, remember);
+
+    sqlx::query!(
+        "INSERT INTO sessions (user_id, token, created_at, last_active_at, expires_at, remember) VALUES ($1, $2, $3, $3, $4, $5)",
+        user_id,
+        token,
+        now,
+        expires_at,
+        remember
+    )
+    .execute(&state.db)
+    .await?;
+
+    let max_age = remember.then_some(state.sessions.max_age);
+    cookies.add(session_cookie(token, max_age));
+
+    Ok(())
+}
+
+/// Like `check_auth`, but only admits signed-in administrators.
+async fn require_admin(cookies: &Cookies, state: &AppState) -> Result<User> {
+    match check_auth(cookies, state).await {
+        Some(user) if user.is_admin => Ok(user),
+        _ => Err(Error::Unauthorized),
+    }
+}
+
 #[derive(Template)]
 #[template(path = "index.html")]
 struct IndexTemplate {
     posts: Vec<PostWithAuthor>,
+    page: i64,
+    total_pages: i64,
+    /// Page the "newer" link points at; clamped so out-of-range pages lead back to real posts.
+    prev_page: i64,
+    /// Posts on this page the signed-in user has upvoted.
+    voted: HashSet<Uuid>,
+    /// Posts on this page published since the signed-in user's last visit.
+    unread: HashSet<Uuid>,
+    /// The one-time greeting for a new user.
+    welcome: Option<String>,
+    avatars: AvatarProvider,
     current_user: Option<String>,
+    csrf_token: String,
+}
+
+impl IndexTemplate {
+    fn has_voted(&self, post: &PostWithAuthor) -> bool {
+        self.voted.contains(&post.id)
+    }
+
+    fn is_unread(&self, post: &PostWithAuthor) -> bool {
+        self.unread.contains(&post.id)
+    }
+}
+
+/// A reply alongside its sanitized, rendered body.
+struct ReplyView {
+    reply: PostWithAuthor,
+    content_html: String,
 }
 
 #[derive(Template)]
 #[template(path = "post.html")]
 struct PostTemplate {
     post: PostWithAuthor,
-    author_gravatar: String,
+    content_html: String,
+    view_count: i64,
+    can_edit: bool,
+    /// The author and admins may look through earlier versions.
+    can_view_history: bool,
+    replies: Vec<ReplyView>,
+    voted: bool,
+    tags: Vec<String>,
+    /// Set right after the reader reported this post.
+    reported: bool,
+    /// Set when the reader's reply was turned away by `validate_post_content`.
+    reply_rejected: bool,
+    avatars: AvatarProvider,
+    current_user: Option<String>,
+    csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "login.html")]
 struct LoginTemplate {
     error: Option<String>,
+    notice: Option<String>,
     current_user: Option<String>,
+    csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "register.html")]
 struct RegisterTemplate {
     error: Option<String>,
+    pow_challenge: String,
+    pow_bits: u32,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "change_password.html")]
+struct ChangePasswordTemplate {
+    error: Option<String>,
+    notice: Option<String>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "account_keys.html")]
+struct AccountKeysTemplate {
+    keys: Vec<AuthorizedKey>,
+    error: Option<String>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "search.html")]
+struct SearchTemplate {
+    query: String,
+    posts: Vec<PostWithAuthor>,
+    avatars: AvatarProvider,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "edit_post.html")]
+struct EditPostTemplate {
+    post: Post,
+    /// The tag field's contents, comma-separated.
+    tags: String,
+    error: Option<String>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "post_history.html")]
+struct PostHistoryTemplate {
+    post: Post,
+    revisions: Vec<PostRevision>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "post_revision.html")]
+struct PostRevisionTemplate {
+    post: Post,
+    revision: PostRevision,
+    /// The kept revision before this one; `None` for the oldest.
+    previous: Option<i32>,
+    title_diff: Vec<DiffLine>,
+    content_diff: Vec<DiffLine>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_auth_log.html")]
+struct AuthLogTemplate {
+    events: Vec<AuthEvent>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_reports.html")]
+struct ReportsTemplate {
+    reports: Vec<ReportWithPost>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "security_alert.html")]
+struct SecurityAlertTemplate {
+    old_ip: String,
+    new_ip: String,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "drafts.html")]
+struct DraftsTemplate {
+    drafts: Vec<Post>,
     current_user: Option<String>,
+    csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "create_post.html")]
 struct CreatePostTemplate {
     error: Option<String>,
+    boards: Vec<Board>,
     current_user: Option<String>,
+    csrf_token: String,
 }
 
-pub async fn index(State(state): State<Arc<AppState>>, cookies: Cookies) -> Result<Response> {
+pub async fn index(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PageQuery>,
+    cookies: Cookies,
+) -> Result<Response> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = state.default_page_size;
+    let offset = (page - 1).saturating_mul(per_page);
+    let user = check_auth(&cookies, &state).await;
+    let user_id = user.as_ref().map(|u| u.id);
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM posts WHERE published = true AND parent_id IS NULL AND deleted_at IS NULL AND board_visible_to(board_id, $1)"#,
+        user_id
+    )
+    .fetch_one(&state.db)
+    .await?;
+
     let posts = sqlx::query_as!(
         PostWithAuthor,
         r#"
         SELECT 
-            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published,
-            p.board_id, b.name as board_name, b.slug as board_slug,
-            u.username as author_username, u.email as author_email
+            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published, p.pinned,
+            p.board_id, b.name as "board_name?", b.slug as "board_slug?", p.parent_id,
+            u.username as author_username, u.display_name as author_display_name, u.email as author_email,
+            (SELECT COUNT(*) FROM post_votes v WHERE v.post_id = p.id) as "vote_count!"
         FROM posts p
         JOIN users u ON p.author_id = u.id
         LEFT JOIN boards b ON p.board_id = b.id
-        WHERE p.published = true
-        ORDER BY p.created_at DESC
-        LIMIT 10
-        "#
+        WHERE p.published = true AND p.parent_id IS NULL AND p.deleted_at IS NULL
+            AND board_visible_to(p.board_id, $3)
+        ORDER BY p.pinned DESC, p.created_at DESC
+        LIMIT $1 OFFSET $2
+        "#,
+        per_page,
+        offset,
+        user_id
     )
     .fetch_all(&state.db)
     .await?;
 
-    let current_user = check_auth(&cookies, &state.db).await.map(|u| u.username);
+    let voted = match &user {
+        Some(user) => {
+            let post_ids: Vec<Uuid> = posts.iter().map(|p| p.id).collect();
+            sqlx::query_scalar!(
+                "SELECT post_id FROM post_votes WHERE user_id = $1 AND post_id = ANY($2)",
+                user.id,
+                &post_ids
+            )
+            .fetch_all(&state.db)
+            .await?
+            .into_iter()
+            .collect()
+        }
+        None => HashSet::new(),
+    };
+    let unread = match &user {
+        Some(user) => {
+            let last_seen_at = db::last_seen_at(&state.db, user.id).await?;
+            posts
+                .iter()
+                .filter(|p| is_unread(p.created_at, last_seen_at))
+                .map(|p| p.id)
+                .collect()
+        }
+        None => HashSet::new(),
+    };
+    let total_pages = ((total + per_page - 1) / per_page).max(1);
+
+    // A failed welcome is not worth an error page; it is simply not shown.
+    let welcome = match (&state.welcome_message, &user) {
+        (Some(message), Some(user)) => match db::take_welcome(&state.db, user.id).await {
+            Ok(true) => {
+                tracing::info!("Welcoming new user {}", user.username);
+                Some(message.clone())
+            }
+            Ok(false) => None,
+            Err(e) => {
+                tracing::warn!("Could not record welcome for {}: {}", user.username, e);
+                None
+            }
+        },
+        _ => None,
+    };
 
     let template = IndexTemplate {
         posts,
-        current_user,
+        page,
+        total_pages,
+        prev_page: (page - 1).min(total_pages),
+        voted,
+        unread,
+        welcome,
+        avatars: state.avatars,
+        current_user: user.map(|u| u.username),
+        csrf_token: ensure_csrf_token(&cookies),
     };
-    Ok(Html(
-        template
-            .render()
-            .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-    )
-    .into_response())
+    let html = template.render()?;
+
+    // Only once this visit's page is rendered, so its new posts still show as new.
+    if let Some(user_id) = user_id {
+        db::mark_seen(&state.db, user_id).await?;
+    }
+    Ok(Html(html).into_response())
 }
 
 pub async fn get_post(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<Uuid>,
+    IdPath(id): IdPath,
+    Query(query): Query<PostQuery>,
+    cookies: Cookies,
+    headers: HeaderMap,
+) -> Result<Response> {
+    render_post(&state, &cookies, &headers, id, query).await
+}
+
+/// `/p/:slug`, the readable address of the page `/posts/:id` shows.
+pub async fn get_post_by_slug(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+    Query(query): Query<PostQuery>,
+    cookies: Cookies,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let id = sqlx::query_scalar!("SELECT id FROM posts WHERE slug = $1", slug)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+    render_post(&state, &cookies, &headers, id, query).await
+}
+
+async fn render_post(
+    state: &AppState,
+    cookies: &Cookies,
+    headers: &HeaderMap,
+    id: Uuid,
+    query: PostQuery,
 ) -> Result<Response> {
+    let user = check_auth(cookies, state).await;
+    let user_id = user.as_ref().map(|u| u.id);
+
+    // Counting and the visibility check happen in one statement so a view is
+    // recorded at most once per request and never for hidden posts.
+    let view_count = sqlx::query_scalar!(
+        "UPDATE posts SET view_count = view_count + 1 WHERE id = $1 AND published = true AND deleted_at IS NULL AND board_visible_to(board_id, $2) RETURNING view_count",
+        id,
+        user_id
+    )
+    .fetch_optional(&state.db)
+    .await?;
+
+    // Drafts are only visible to their author, as a preview. Posts in a
+    // private board look missing to non-members rather than forbidden.
     let post = sqlx::query_as!(
         PostWithAuthor,
         r#"
         SELECT 
-            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published,
-            p.board_id, b.name as board_name, b.slug as board_slug,
-            u.username as author_username, u.email as author_email
+            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published, p.pinned,
+            p.board_id, b.name as "board_name?", b.slug as "board_slug?", p.parent_id,
+            u.username as author_username, u.display_name as author_display_name, u.email as author_email,
+            (SELECT COUNT(*) FROM post_votes v WHERE v.post_id = p.id) as "vote_count!"
+        FROM posts p
+        JOIN users u ON p.author_id = u.id
+        LEFT JOIN boards b ON p.board_id = b.id
+        WHERE p.id = $1 AND p.deleted_at IS NULL AND (p.published = true OR p.author_id = $2)
+            AND board_visible_to(p.board_id, $2)
+        "#,
+        id,
+        user_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+    let view_count = view_count.unwrap_or(0);
+
+    let replies = sqlx::query_as!(
+        PostWithAuthor,
+        r#"
+        SELECT 
+            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published, p.pinned,
+            p.board_id, b.name as "board_name?", b.slug as "board_slug?", p.parent_id,
+            u.username as author_username, u.display_name as author_display_name, u.email as author_email,
+            (SELECT COUNT(*) FROM post_votes v WHERE v.post_id = p.id) as "vote_count!"
         FROM posts p
         JOIN users u ON p.author_id = u.id
         LEFT JOIN boards b ON p.board_id = b.id
-        WHERE p.id = $1 AND p.published = true
+        WHERE p.parent_id = $1 AND p.published = true AND p.deleted_at IS NULL
+        ORDER BY p.created_at ASC
         "#,
         id
     )
+    .fetch_all(&state.db)
+    .await?;
+
+    let can_edit = user.as_ref().is_some_and(|u| u.id == post.author_id);
+    let can_view_history = can_edit || user.as_ref().is_some_and(|u| u.is_admin);
+    let voted = match user_id {
+        Some(user_id) => sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM post_votes WHERE post_id = $1 AND user_id = $2) as "exists!""#,
+            id,
+            user_id
+        )
+        .fetch_one(&state.db)
+        .await?,
+        None => false,
+    };
+    let tags = db::post_tags(&state.db, id).await?;
+    let reported = query.reported.is_some();
+    let reply_rejected = query.reply_rejected.is_some();
+    let current_user = user.map(|u| u.username);
+    let csrf_token = ensure_csrf_token(cookies);
+
+    // Everything the page shows except the view count, which would make
+    // every copy stale; a revalidated page may show a slightly lower one.
+    let validator = serde_json::to_vec(&(
+        &post,
+        &replies,
+        voted,
+        &tags,
+        reported,
+        reply_rejected,
+        &current_user,
+        &csrf_token,
+        can_view_history,
+    ))
+    .map_err(|e| Error::Internal(e.to_string()))?;
+
+    let mut response = etag::respond(headers, etag::weak(&validator), || {
+        let content_html = markdown::render(&post.content);
+        let replies = replies
+            .into_iter()
+            .map(|reply| ReplyView {
+                content_html: markdown::render(&reply.content),
+                reply,
+            })
+            .collect();
+
+        let template = PostTemplate {
+            post,
+            content_html,
+            view_count,
+            can_edit,
+            can_view_history,
+            replies,
+            voted,
+            tags,
+            reported,
+            reply_rejected,
+            avatars: state.avatars,
+            current_user,
+            csrf_token,
+        };
+        Ok(Html(template.render()?).into_response())
+    })?;
+    // The page differs per reader, so only the browser may keep it, and it
+    // should check back each time.
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("private, no-cache"),
+    );
+    Ok(response)
+}
+
+/// Latest published top-level posts for a feed, from one board or all.
+/// Feed readers carry no session, so private boards are always left out.
+async fn feed_posts(db: &sqlx::PgPool, board_id: Option<Uuid>) -> Result<Vec<PostWithAuthor>> {
+    let posts = sqlx::query_as!(
+        PostWithAuthor,
+        r#"
+        SELECT 
+            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published, p.pinned,
+            p.board_id, b.name as "board_name?", b.slug as "board_slug?", p.parent_id,
+            u.username as author_username, u.display_name as author_display_name, u.email as author_email,
+            (SELECT COUNT(*) FROM post_votes v WHERE v.post_id = p.id) as "vote_count!"
+        FROM posts p
+        JOIN users u ON p.author_id = u.id
+        LEFT JOIN boards b ON p.board_id = b.id
+        WHERE p.published = true AND p.parent_id IS NULL AND p.deleted_at IS NULL
+            AND ($2::uuid IS NULL OR p.board_id = $2)
+            AND board_visible_to(p.board_id, NULL)
+        ORDER BY p.created_at DESC
+        LIMIT $1
+        "#,
+        feed::FEED_LIMIT,
+        board_id
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(posts)
+}
+
+/// Where feed links point: the host the request was made to.
+fn feed_base_url(headers: &HeaderMap) -> String {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost");
+    format!("http://{}", host)
+}
+
+/// The feed, or `304 Not Modified` when the reader already has this version.
+fn rss_response(headers: &HeaderMap, xml: String) -> Result<Response> {
+    etag::respond(headers, etag::strong(xml.as_bytes()), || {
+        Ok((
+            [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+            xml,
+        )
+            .into_response())
+    })
+}
+
+pub async fn rss_feed(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Result<Response> {
+    let posts = feed_posts(&state.db, None).await?;
+    let xml = feed::render_rss(&posts, &feed_base_url(&headers))?;
+    rss_response(&headers, xml)
+}
+
+pub async fn board_feed(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let board = sqlx::query_as!(
+        Board,
+        "SELECT * FROM boards WHERE slug = $1 AND visibility = 'public'",
+        slug
+    )
     .fetch_optional(&state.db)
     .await?
     .ok_or(Error::NotFound)?;
 
-    let author_gravatar = post.author_gravatar(64);
+    let posts = feed_posts(&state.db, Some(board.id)).await?;
+    let xml = feed::render_board_rss(&board, &posts, &feed_base_url(&headers))?;
+    rss_response(&headers, xml)
+}
 
-    let template = PostTemplate {
-        post,
-        author_gravatar,
+/// Serves `/avatar/<username>.svg`. Identicons depend only on the name, so
+/// unknown users get one too and nothing is looked up.
+pub async fn avatar(
+    State(state): State<Arc<AppState>>,
+    Path(file): Path<String>,
+) -> Result<Response> {
+    let username = file.strip_suffix(".svg").ok_or(Error::NotFound)?;
+    let svg = match state.avatars {
+        AvatarProvider::Disabled => PLACEHOLDER_SVG.to_string(),
+        AvatarProvider::Gravatar | AvatarProvider::Local => identicon_svg(username),
     };
-    Ok(Html(
-        template
-            .render()
-            .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        svg,
     )
-    .into_response())
+        .into_response())
+}
+
+pub async fn search_posts(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchQuery>,
+    cookies: Cookies,
+) -> Result<Response> {
+    let query = params.q.unwrap_or_default().trim().to_string();
+    let user = check_auth(&cookies, &state).await;
+
+    let posts = if query.is_empty() {
+        Vec::new()
+    } else {
+        sqlx::query_as!(
+            PostWithAuthor,
+            r#"
+            SELECT 
+                p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published, p.pinned,
+                p.board_id, b.name as "board_name?", b.slug as "board_slug?", p.parent_id,
+                u.username as author_username, u.display_name as author_display_name, u.email as author_email,
+                (SELECT COUNT(*) FROM post_votes v WHERE v.post_id = p.id) as "vote_count!"
+            FROM posts p
+            JOIN users u ON p.author_id = u.id
+            LEFT JOIN boards b ON p.board_id = b.id
+            WHERE p.published = true AND p.deleted_at IS NULL
+              AND board_visible_to(p.board_id, $2)
+              AND to_tsvector('english', p.title || ' ' || p.content) @@ plainto_tsquery('english', $1)
+            ORDER BY ts_rank(
+                to_tsvector('english', p.title || ' ' || p.content),
+                plainto_tsquery('english', $1)
+            ) DESC, p.created_at DESC
+            LIMIT 50
+            "#,
+            query,
+            user.as_ref().map(|u| u.id)
+        )
+        .fetch_all(&state.db)
+        .await?
+    };
+
+    let current_user = user.map(|u| u.username);
+
+    let template = SearchTemplate {
+        query,
+        posts,
+        avatars: state.avatars,
+        current_user,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
 }
 
 pub async fn login_form(cookies: Cookies, State(state): State<Arc<AppState>>) -> Result<Response> {
-    let current_user = check_auth(&cookies, &state.db).await.map(|u| u.username);
+    let current_user = check_auth(&cookies, &state).await.map(|u| u.username);
 
     let template = LoginTemplate {
         error: None,
+        notice: None,
         current_user,
+        csrf_token: ensure_csrf_token(&cookies),
     };
-    Ok(Html(
-        template
-            .render()
-            .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-    )
-    .into_response())
+    Ok(Html(template.render()?).into_response())
 }
 
-pub async fn login_submit(
-    State(state): State<Arc<AppState>>,
-    cookies: Cookies,
-    Form(payload): Form<AuthPayload>,
-) -> Result<Response> {
-    let user =
-        AuthService::authenticate_user(&state.db, &payload.username, &payload.password).await?;
+fn login_error(cookies: &Cookies, status: StatusCode, message: String) -> Result<Response> {
+    let template = LoginTemplate {
+        error: Some(message),
+        notice: None,
+        current_user: None,
+        csrf_token: ensure_csrf_token(cookies),
+    };
+    Ok((status, Html(template.render()?)).into_response())
+}
+
+/// What became of a login attempt, shared by the form and JSON logins so
+/// both apply the same lockout, auditing and session policy.
+enum LoginOutcome {
+    LockedOut(String),
+    InvalidCredentials,
+    Unverified,
+    /// A session was started. `previous_ip` is set when the user last signed
+    /// in from a different address.
+    SignedIn {
+        user: User,
+        previous_ip: Option<String>,
+    },
+}
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+const UNVERIFIED_LOGIN_MESSAGE: &str =
+    "Please verify your email address before logging in. Check your inbox for the verification link.";
+
+/// The address a request came from. Behind a trusted reverse proxy
+/// (`trust_proxy`) that is the last `X-Forwarded-For` entry, the one the
+/// proxy appended itself; earlier entries come from the client and could be
+/// forged. Otherwise, or when the header is missing or malformed, it is the
+/// peer of the TCP connection.
+pub(crate) fn client_ip(
+    headers: &HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    trust_proxy: bool,
+) -> IpAddr {
+    let forwarded = trust_proxy
+        .then(|| headers.get_all(X_FORWARDED_FOR).iter().next_back())
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse::<IpAddr>().ok());
+
+    forwarded
+        .or(connect_info.map(|ConnectInfo(addr)| addr.ip()))
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+}
+
+async fn attempt_login(
+    state: &AppState,
+    cookies: &Cookies,
+    client_ip: IpAddr,
+    user_agent: Option<&str>,
+    payload: &AuthPayload,
+) -> Result<LoginOutcome> {
+    if let Some(remaining) = state.login_limiter.time_until_allowed(client_ip) {
+        tracing::warn!("Rejecting login from locked out address {}", client_ip);
+        return Ok(LoginOutcome::LockedOut(lockout_message(remaining)));
+    }
+
+    let user = AuthService::authenticate_user(
+        &state.db,
+        &payload.username,
+        &payload.password,
+        &state.password_params,
+    )
+    .await?;
 
     let user = match user {
         Some(u) => u,
         None => {
-            let template = LoginTemplate {
-                error: Some("Invalid username or password".to_string()),
-                current_user: None,
-            };
-            return Ok(Html(
-                template
-                    .render()
-                    .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
+            state.login_limiter.record(client_ip);
+            AuthService::record_event(
+                &state.db,
+                AuthEventKind::WebLoginFailure,
+                None,
+                &payload.username,
+                &client_ip.to_string(),
+                user_agent,
             )
-            .into_response());
+            .await;
+            return Ok(LoginOutcome::InvalidCredentials);
         }
     };
 
-    let token = AuthService::generate_session_token();
-    let expires_at = Utc::now() + Duration::days(7);
+    state.login_limiter.reset(client_ip);
 
-    sqlx::query!(
-        "INSERT INTO sessions (user_id, token, expires_at) VALUES ($1, $2, $3)",
-        user.id,
-        token,
-        expires_at
-    )
-    .execute(&state.db)
-    .await?;
+    if !user.is_verified() {
+        return Ok(LoginOutcome::Unverified);
+    }
 
-    let mut cookie = Cookie::new("session_id", token);
-    cookie.set_path("/");
-    cookie.set_http_only(true);
-    cookies.add(cookie);
+    start_session(state, cookies, user.id, payload.remember).await?;
 
-    Ok(Redirect::to("/").into_response())
+    AuthService::record_event(
+        &state.db,
+        AuthEventKind::WebLoginSuccess,
+        Some(user.id),
+        &user.username,
+        &client_ip.to_string(),
+        user_agent,
+    )
+    .await;
+
+    let current_ip = client_ip.to_string();
+    let previous_ip = AuthService::record_login_ip(&state.db, &user, &current_ip).await?;
+    if let Some(old_ip) = &previous_ip {
+        tracing::warn!(
+            "Login for {} from new IP {} (previously {})",
+            user.username,
+            current_ip,
+            old_ip
+        );
+    }
+
+    Ok(LoginOutcome::SignedIn { user, previous_ip })
 }
 
-pub async fn register_form(
-    cookies: Cookies,
+pub async fn login_submit(
     State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    cookies: Cookies,
+    Form(payload): Form<AuthPayload>,
 ) -> Result<Response> {
-    let current_user = check_auth(&cookies, &state.db).await.map(|u| u.username);
+    let client_ip = client_ip(&headers, connect_info, state.trust_proxy);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok());
+
+    match attempt_login(&state, &cookies, client_ip, user_agent, &payload).await? {
+        LoginOutcome::LockedOut(message) => {
+            login_error(&cookies, StatusCode::TOO_MANY_REQUESTS, message)
+        }
+        LoginOutcome::InvalidCredentials => login_error(
+            &cookies,
+            StatusCode::OK,
+            "Invalid username or password".to_string(),
+        ),
+        LoginOutcome::Unverified => login_error(
+            &cookies,
+            StatusCode::FORBIDDEN,
+            UNVERIFIED_LOGIN_MESSAGE.to_string(),
+        ),
+        LoginOutcome::SignedIn {
+            previous_ip: Some(old_ip),
+            ..
+        } => {
+            let alert_value = format!("{}|{}", old_ip, client_ip);
+            let mut alert = Cookie::new(SECURITY_ALERT_COOKIE, alert_value);
+            alert.set_path("/security-alert");
+            alert.set_http_only(true);
+            alert.set_secure(true);
+            alert.set_same_site(SameSite::Lax);
+            cookies.add(alert);
+            Ok(Redirect::to("/security-alert").into_response())
+        }
+        LoginOutcome::SignedIn { .. } => Ok(Redirect::to("/").into_response()),
+    }
+}
 
-    let template = RegisterTemplate {
-        error: None,
-        current_user,
-    };
-    Ok(Html(
-        template
-            .render()
-            .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-    )
-    .into_response())
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub username: String,
+    /// Set when this login came from a different IP than the previous one.
+    pub previous_ip: Option<String>,
 }
 
-pub async fn register_submit(
+/// JSON counterpart of `login_submit` for script and mobile clients. The
+/// session travels in the same `session_id` cookie as a browser login.
+pub async fn api_login(
     State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     cookies: Cookies,
-    Form(payload): Form<RegisterPayload>,
-) -> Result<Response> {
-    if payload.username.len() < 3 {
-        let template = RegisterTemplate {
-            error: Some("Username must be at least 3 characters".to_string()),
-            current_user: None,
-        };
-        return Ok(Html(
-            template
-                .render()
-                .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-        )
-        .into_response());
+    Json(payload): Json<AuthPayload>,
+) -> Result<Json<LoginResponse>> {
+    let client_ip = client_ip(&headers, connect_info, state.trust_proxy);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok());
+
+    match attempt_login(&state, &cookies, client_ip, user_agent, &payload).await? {
+        LoginOutcome::LockedOut(message) => Err(Error::TooManyRequests(message)),
+        LoginOutcome::InvalidCredentials => Err(Error::Unauthorized),
+        LoginOutcome::Unverified => Err(Error::Forbidden(UNVERIFIED_LOGIN_MESSAGE.to_string())),
+        LoginOutcome::SignedIn { user, previous_ip } => Ok(Json(LoginResponse {
+            username: user.username,
+            previous_ip,
+        })),
     }
+}
 
-    if payload.password.len() < 8 {
-        let template = RegisterTemplate {
-            error: Some("Password must be at least 8 characters".to_string()),
-            current_user: None,
-        };
-        return Ok(Html(
-            template
-                .render()
-                .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-        )
-        .into_response());
-    }
+/// The signed-in user as `/api/me` reports them. It is always the caller's
+/// own record, so the email is included; the password hash never is.
+#[derive(Debug, Serialize)]
+pub struct CurrentUser {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: String,
+    pub created_at: DateTime<Utc>,
+    pub is_admin: bool,
+}
 
-    let existing_user = sqlx::query!(
-        "SELECT id FROM users WHERE username = $1 OR email = $2",
-        payload.username,
-        payload.email
-    )
-    .fetch_optional(&state.db)
-    .await?;
+pub async fn api_me(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CurrentUser>> {
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
 
-    if existing_user.is_some() {
-        let template = RegisterTemplate {
-            error: Some("Username or email already exists".to_string()),
-            current_user: None,
-        };
-        return Ok(Html(
-            template
-                .render()
-                .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
+    Ok(Json(CurrentUser {
+        id: user.id,
+        username: user.username,
+        display_name: user.display_name,
+        email: user.email,
+        created_at: user.created_at,
+        is_admin: user.is_admin,
+    }))
+}
+
+/// Carries the old and new login IPs from `login_submit` to the alert page.
+const SECURITY_ALERT_COOKIE: &str = "security_alert";
+
+pub async fn security_alert(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let Some(alert) = cookies.get(SECURITY_ALERT_COOKIE) else {
+        return Ok(Redirect::to("/").into_response());
+    };
+    let (old_ip, new_ip) = match alert.value().split_once('|') {
+        Some((old_ip, new_ip)) => (old_ip.to_string(), new_ip.to_string()),
+        None => return Ok(Redirect::to("/").into_response()),
+    };
+
+    // Shown once; reloading the page continues to the homepage.
+    let mut removal = Cookie::from(SECURITY_ALERT_COOKIE);
+    removal.set_path("/security-alert");
+    cookies.remove(removal);
+
+    let template = SecurityAlertTemplate {
+        old_ip,
+        new_ip,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+/// Carries the proof-of-work challenge from the sign-up form to `register_submit`.
+const REGISTER_CHALLENGE_COOKIE: &str = "register_challenge";
+
+/// Renders the sign-up form with a fresh proof-of-work challenge.
+fn register_page(
+    state: &AppState,
+    cookies: &Cookies,
+    current_user: Option<String>,
+    error: Option<String>,
+) -> Result<Response> {
+    let pow_challenge = state.registration_challenges.issue();
+
+    let mut cookie = Cookie::new(REGISTER_CHALLENGE_COOKIE, pow_challenge.clone());
+    cookie.set_path("/register");
+    cookie.set_http_only(true);
+    cookie.set_secure(true);
+    cookie.set_same_site(SameSite::Strict);
+    cookie.set_max_age(tower_cookies::cookie::time::Duration::seconds(
+        CHALLENGE_TTL.as_secs() as i64,
+    ));
+    cookies.add(cookie);
+
+    let template = RegisterTemplate {
+        error,
+        pow_challenge,
+        pow_bits: state.registration_pow_bits,
+        current_user,
+        csrf_token: ensure_csrf_token(cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+/// Checks the sign-up fields that don't need the database.
+fn validate_registration(payload: &RegisterPayload) -> Result<()> {
+    let mut fields = Vec::new();
+    if payload.username.len() < 3 {
+        fields.push((
+            "username".to_string(),
+            "Username must be at least 3 characters".to_string(),
+        ));
+    }
+    if let Err(reason) = validate_password_strength(&payload.password) {
+        fields.push(("password".to_string(), reason));
+    }
+    Error::validation(fields)
+}
+
+/// Separates a validation failure, which a form shows above its fields,
+/// from errors that should fail the request.
+fn form_error<T>(result: Result<T>) -> Result<std::result::Result<T, String>> {
+    match result {
+        Ok(value) => Ok(Ok(value)),
+        Err(Error::Validation(fields)) => Ok(Err(fields
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect::<Vec<_>>()
+            .join(". "))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn register_form(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let current_user = check_auth(&cookies, &state).await.map(|u| u.username);
+    register_page(&state, &cookies, current_user, None)
+}
+
+pub async fn register_submit(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    cookies: Cookies,
+    Form(payload): Form<RegisterPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    // Checked first: it costs one hash, while the rest hits the database.
+    let challenge = cookies
+        .get(REGISTER_CHALLENGE_COOKIE)
+        .map(|c| c.value().to_string());
+    let solved = match (challenge, payload.pow_nonce.as_deref()) {
+        (Some(challenge), Some(nonce)) => {
+            state.registration_challenges.redeem(&challenge)
+                && verify_pow(&challenge, nonce, state.registration_pow_bits)
+        }
+        _ => false,
+    };
+    if !solved {
+        tracing::warn!("Rejecting registration without a valid proof-of-work");
+        return register_page(
+            &state,
+            &cookies,
+            None,
+            Some("The sign-up check expired or failed. Please try again.".to_string()),
+        );
+    }
+
+    if let Err(message) = form_error(validate_registration(&payload))? {
+        return register_page(&state, &cookies, None, Some(message));
+    }
+
+    let existing_user = sqlx::query!(
+        "SELECT id FROM users WHERE username = $1 OR email = $2",
+        payload.username,
+        payload.email
+    )
+    .fetch_optional(&state.db)
+    .await?;
+
+    if existing_user.is_some() {
+        return register_page(
+            &state,
+            &cookies,
+            None,
+            Some("Username or email already exists".to_string()),
+        );
+    }
+
+    let password_hash = AuthService::hash_password(&payload.password, &state.password_params)?;
+
+    let user = sqlx::query_as!(
+        User,
+        "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING id, username, email, password_hash, created_at, last_login_ip, last_login_at, verified_at, is_admin, display_name, bio",
+        payload.username,
+        payload.email,
+        password_hash
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    let token = AuthService::generate_session_token();
+
+    sqlx::query!(
+        "INSERT INTO email_verifications (user_id, token) VALUES ($1, $2)",
+        user.id,
+        token
+    )
+    .execute(&state.db)
+    .await?;
+
+    // No mailer yet: log the link so it can be copied during development.
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost");
+    tracing::info!(
+        "Verification link for {} <{}>: http://{}/verify/{}",
+        user.username,
+        user.email,
+        host,
+        token
+    );
+
+    let template = LoginTemplate {
+        error: None,
+        notice: Some(format!(
+            "Account created. We sent a verification link to {}; verify your email before logging in.",
+            user.email
+        )),
+        current_user: None,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn verify_email(
+    State(state): State<Arc<AppState>>,
+    cookies: Cookies,
+    Path(token): Path<String>,
+) -> Result<Response> {
+    let verification = sqlx::query!(
+        "DELETE FROM email_verifications WHERE token = $1 RETURNING user_id, expires_at",
+        token
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if verification.expires_at < Utc::now() {
+        return Err(Error::NotFound);
+    }
+
+    sqlx::query!(
+        "UPDATE users SET verified_at = NOW() WHERE id = $1 AND verified_at IS NULL",
+        verification.user_id
+    )
+    .execute(&state.db)
+    .await?;
+
+    let template = LoginTemplate {
+        error: None,
+        notice: Some("Email verified. You can now log in.".to_string()),
+        current_user: None,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+/// How long a password reset link stays usable.
+const PASSWORD_RESET_TTL_MINUTES: i64 = 30;
+
+/// Shown after every reset request, so it reveals nothing about which
+/// emails have accounts.
+const PASSWORD_RESET_SENT: &str =
+    "If an account uses that email, we sent it a link to reset the password. The link expires in 30 minutes.";
+
+#[derive(Template)]
+#[template(path = "forgot_password.html")]
+struct ForgotPasswordTemplate {
+    notice: Option<String>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "reset_password.html")]
+struct ResetPasswordTemplate {
+    token: String,
+    error: Option<String>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+pub async fn forgot_password_form(cookies: Cookies) -> Result<Response> {
+    let template = ForgotPasswordTemplate {
+        notice: None,
+        current_user: None,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn forgot_password_submit(
+    State(state): State<Arc<AppState>>,
+    cookies: Cookies,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Form(payload): Form<ForgotPasswordPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    // Counted per address whether or not the email has an account, so the
+    // limit reveals nothing about which ones do.
+    let client_ip = client_ip(&headers, connect_info, state.trust_proxy);
+    if state.password_reset_limiter.try_record(client_ip).is_err() {
+        tracing::warn!(
+            "Rejecting password reset request from rate-limited address {}",
+            client_ip
+        );
+        return Err(Error::TooManyRequests(
+            "Too many password reset requests from your address. Try again later.".to_string(),
+        ));
+    }
+
+    let user = sqlx::query!(
+        "SELECT id, username, email FROM users WHERE LOWER(email) = LOWER($1)",
+        payload.email.trim()
+    )
+    .fetch_optional(&state.db)
+    .await?;
+
+    if let Some(user) = user {
+        let token = AuthService::generate_session_token();
+        let expires_at = Utc::now() + Duration::minutes(PASSWORD_RESET_TTL_MINUTES);
+
+        // Only the newest link works.
+        let mut tx = state.db.begin().await?;
+        sqlx::query!(
+            "DELETE FROM password_reset_tokens WHERE user_id = $1",
+            user.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO password_reset_tokens (user_id, token, expires_at) VALUES ($1, $2, $3)",
+            user.id,
+            token,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        // No mailer yet: log the link so it can be copied during development.
+        let host = headers
+            .get(header::HOST)
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("localhost");
+        tracing::info!(
+            "Password reset link for {} <{}>: http://{}/reset-password/{}",
+            user.username,
+            user.email,
+            host,
+            token
+        );
+    }
+
+    let template = ForgotPasswordTemplate {
+        notice: Some(PASSWORD_RESET_SENT.to_string()),
+        current_user: None,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+fn render_reset_password(
+    cookies: &Cookies,
+    token: String,
+    error: Option<String>,
+) -> Result<Response> {
+    let template = ResetPasswordTemplate {
+        token,
+        error,
+        current_user: None,
+        csrf_token: ensure_csrf_token(cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn reset_password_form(
+    State(state): State<Arc<AppState>>,
+    cookies: Cookies,
+    Path(token): Path<String>,
+) -> Result<Response> {
+    let valid = sqlx::query_scalar!(
+        r#"SELECT EXISTS(
+            SELECT 1 FROM password_reset_tokens WHERE token = $1 AND expires_at > NOW()
+        ) AS "valid!""#,
+        token
+    )
+    .fetch_one(&state.db)
+    .await?;
+    if !valid {
+        return Err(Error::NotFound);
+    }
+
+    render_reset_password(&cookies, token, None)
+}
+
+pub async fn reset_password_submit(
+    State(state): State<Arc<AppState>>,
+    cookies: Cookies,
+    Path(token): Path<String>,
+    Form(payload): Form<ResetPasswordPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    // Checked before the token is spent, so a typo doesn't burn the link.
+    if payload.new_password != payload.confirm_password {
+        return render_reset_password(&cookies, token, Some("Passwords do not match".to_string()));
+    }
+    if let Err(reason) = validate_password_strength(&payload.new_password) {
+        return render_reset_password(&cookies, token, Some(reason));
+    }
+
+    let password_hash = AuthService::hash_password(&payload.new_password, &state.password_params)?;
+
+    let mut tx = state.db.begin().await?;
+    let reset = sqlx::query!(
+        "DELETE FROM password_reset_tokens WHERE token = $1 RETURNING user_id, expires_at",
+        token
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if reset.expires_at < Utc::now() {
+        tx.commit().await?;
+        return Err(Error::NotFound);
+    }
+
+    let username = sqlx::query_scalar!(
+        "UPDATE users SET password_hash = $1 WHERE id = $2 RETURNING username",
+        password_hash,
+        reset.user_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Whoever knew the old password is signed out too.
+    let revoked = sqlx::query!("DELETE FROM sessions WHERE user_id = $1", reset.user_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+
+    tracing::info!(
+        "Password reset for {}; revoked {} sessions",
+        username,
+        revoked
+    );
+
+    let template = LoginTemplate {
+        error: None,
+        notice: Some("Password reset. You can now log in with your new password.".to_string()),
+        current_user: None,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+fn render_change_password(
+    cookies: &Cookies,
+    username: String,
+    error: Option<String>,
+    notice: Option<String>,
+) -> Result<Response> {
+    let template = ChangePasswordTemplate {
+        error,
+        notice,
+        current_user: Some(username),
+        csrf_token: ensure_csrf_token(cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn change_password_form(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    render_change_password(&cookies, user.username, None, None)
+}
+
+pub async fn change_password_submit(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    Form(payload): Form<ChangePasswordPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    if !AuthService::verify_password(&payload.current_password, &user.password_hash)? {
+        return render_change_password(
+            &cookies,
+            user.username,
+            Some("Current password is incorrect".to_string()),
+            None,
+        );
+    }
+
+    if payload.new_password != payload.confirm_password {
+        return render_change_password(
+            &cookies,
+            user.username,
+            Some("New passwords do not match".to_string()),
+            None,
+        );
+    }
+
+    if let Err(reason) = validate_password_strength(&payload.new_password) {
+        return render_change_password(&cookies, user.username, Some(reason), None);
+    }
+
+    let password_hash = AuthService::hash_password(&payload.new_password, &state.password_params)?;
+
+    sqlx::query!(
+        "UPDATE users SET password_hash = $1 WHERE id = $2",
+        password_hash,
+        user.id
+    )
+    .execute(&state.db)
+    .await?;
+
+    // check_auth succeeded, so the session cookie is present.
+    let current_token = cookies
+        .get("session_id")
+        .map(|c| c.value().to_string())
+        .unwrap_or_default();
+
+    let revoked = sqlx::query!(
+        "DELETE FROM sessions WHERE user_id = $1 AND token <> $2",
+        user.id,
+        current_token
+    )
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    tracing::info!(
+        "Password changed for {}; revoked {} other sessions",
+        user.username,
+        revoked
+    );
+
+    render_change_password(
+        &cookies,
+        user.username,
+        None,
+        Some("Password changed. Your other sessions have been signed out.".to_string()),
+    )
+}
+
+#[derive(Template)]
+#[template(path = "account.html")]
+struct AccountSettingsTemplate {
+    joined_at: DateTime<Utc>,
+    last_login_at: Option<DateTime<Utc>>,
+    last_login_ip: Option<String>,
+    counts: PostCounts,
+    display_name: String,
+    bio: String,
+    error: Option<String>,
+    notice: Option<String>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+const PROFILE_RECENT_POSTS: i64 = 20;
+
+#[derive(Template)]
+#[template(path = "user_profile.html")]
+struct UserProfileTemplate {
+    profile: User,
+    posts: Vec<Post>,
+    avatars: AvatarProvider,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+/// The account page: an overview of the signed-in user's own row and posts,
+/// then the profile form.
+async fn render_account_settings(
+    state: &AppState,
+    cookies: &Cookies,
+    user: User,
+    error: Option<String>,
+    notice: Option<String>,
+) -> Result<Response> {
+    let counts = db::count_user_posts(&state.db, user.id).await?;
+    let template = AccountSettingsTemplate {
+        joined_at: user.created_at,
+        last_login_at: user.last_login_at,
+        last_login_ip: user.last_login_ip,
+        counts,
+        display_name: user.display_name.unwrap_or_default(),
+        bio: user.bio.unwrap_or_default(),
+        error,
+        notice,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn account_settings_form(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    render_account_settings(&state, &cookies, user, None, None).await
+}
+
+pub async fn account_settings_submit(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    Form(payload): Form<AccountSettingsPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let Some(mut user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let cleaned = normalize_display_name(&payload.display_name)
+        .and_then(|name| Ok((name, normalize_bio(&payload.bio)?)));
+    let (display_name, bio) = match cleaned {
+        Ok(cleaned) => cleaned,
+        Err(reason) => {
+            // Echo the submission back so the user can shorten it.
+            user.display_name = Some(payload.display_name);
+            user.bio = Some(payload.bio);
+            return render_account_settings(&state, &cookies, user, Some(reason), None).await;
+        }
+    };
+
+    sqlx::query!(
+        "UPDATE users SET display_name = $1, bio = $2 WHERE id = $3",
+        display_name,
+        bio,
+        user.id
+    )
+    .execute(&state.db)
+    .await?;
+
+    user.display_name = display_name;
+    user.bio = bio;
+    render_account_settings(
+        &state,
+        &cookies,
+        user,
+        None,
+        Some("Profile saved".to_string()),
+    )
+    .await
+}
+
+pub async fn user_profile(
+    State(state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+    cookies: Cookies,
+) -> Result<Response> {
+    let profile = sqlx::query_as!(
+        User,
+        "SELECT id, username, email, password_hash, created_at, last_login_ip, last_login_at, verified_at, is_admin, display_name, bio FROM users WHERE username = $1",
+        username
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    let user = check_auth(&cookies, &state).await;
+    let posts = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE author_id = $1 AND published = true AND parent_id IS NULL AND deleted_at IS NULL AND board_visible_to(board_id, $3) ORDER BY created_at DESC LIMIT $2",
+        profile.id,
+        PROFILE_RECENT_POSTS,
+        user.as_ref().map(|u| u.id)
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let current_user = user.map(|u| u.username);
+
+    let template = UserProfileTemplate {
+        profile,
+        posts,
+        avatars: state.avatars,
+        current_user,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+async fn render_account_keys(
+    cookies: &Cookies,
+    db: &sqlx::PgPool,
+    user: User,
+    error: Option<String>,
+) -> Result<Response> {
+    let keys = sqlx::query_as!(
+        AuthorizedKey,
+        "SELECT id, user_id, public_key, key_type, comment, created_at FROM authorized_keys WHERE user_id = $1 ORDER BY created_at ASC",
+        user.id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let template = AccountKeysTemplate {
+        keys,
+        error,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+/// Deletes the signed-in account after the password is re-entered. See
+/// `db::anonymize_user` for what is kept and what is removed.
+pub async fn delete_account(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<DeleteAccountPayload>,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
+
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    if !AuthService::verify_password(&payload.password, &user.password_hash)? {
+        return Err(Error::Forbidden("Password is incorrect".to_string()));
+    }
+
+    // A hash of a random secret nobody holds, so the row can never log in.
+    let unusable_password_hash =
+        AuthService::hash_password(&Uuid::new_v4().to_string(), &state.password_params)?;
+    db::anonymize_user(&state.db, user.id, &unusable_password_hash).await?;
+    cookies.remove(Cookie::from("session_id"));
+
+    tracing::info!("Account {} ({}) deleted", user.username, user.id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Downloads everything stored about the signed-in user as JSON. Posts are
+/// the user's own only, so other people's replies never appear in it.
+pub async fn export_account(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let posts = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE author_id = $1 ORDER BY created_at ASC",
+        user.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let authorized_keys = sqlx::query_as!(
+        AuthorizedKey,
+        "SELECT id, user_id, public_key, key_type, comment, created_at FROM authorized_keys WHERE user_id = $1 ORDER BY created_at ASC",
+        user.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let current_token = cookies
+        .get("session_id")
+        .map(|c| c.value().to_string())
+        .unwrap_or_default();
+    let sessions = sqlx::query_as!(
+        SessionInfo,
+        r#"SELECT id, created_at, last_active_at, expires_at, token = $2 as "current!" FROM sessions WHERE user_id = $1 AND expires_at > NOW() ORDER BY created_at ASC"#,
+        user.id,
+        current_token
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    tracing::info!("Account data exported by {}", user.username);
+
+    let export = AccountExport {
+        exported_at: Utc::now(),
+        profile: user.into(),
+        posts,
+        authorized_keys,
+        sessions,
+    };
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"export.json\"",
+        )],
+        Json(export),
+    )
+        .into_response())
+}
+
+pub async fn list_keys(cookies: Cookies, State(state): State<Arc<AppState>>) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    render_account_keys(&cookies, &state.db, user, None).await
+}
+
+pub async fn add_key(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    Form(payload): Form<AddKeyPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let parsed = match parse_openssh_public_key(&payload.public_key) {
+        Ok(parsed) => parsed,
+        Err(reason) => {
+            return render_account_keys(&cookies, &state.db, user, Some(reason)).await;
+        }
+    };
+
+    if parsed
+        .comment
+        .as_ref()
+        .is_some_and(|c| c.chars().count() > 255)
+    {
+        return render_account_keys(
+            &cookies,
+            &state.db,
+            user,
+            Some("Key comment must be at most 255 characters".to_string()),
+        )
+        .await;
+    }
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO authorized_keys (user_id, public_key, key_type, comment)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, public_key) DO NOTHING
+        "#,
+        user.id,
+        parsed.public_key,
+        parsed.key_type,
+        parsed.comment
+    )
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    if inserted == 0 {
+        return render_account_keys(
+            &cookies,
+            &state.db,
+            user,
+            Some("That key is already registered".to_string()),
+        )
+        .await;
+    }
+
+    tracing::info!("Added {} key for {}", parsed.key_type, user.username);
+
+    Ok(Redirect::to("/account/keys").into_response())
+}
+
+pub async fn delete_key(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
+
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    let existing_key = sqlx::query!("SELECT user_id FROM authorized_keys WHERE id = $1", id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if user.id != existing_key.user_id {
+        return Err(Error::Unauthorized);
+    }
+
+    sqlx::query!("DELETE FROM authorized_keys WHERE id = $1", id)
+        .execute(&state.db)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deletes the current session, if any, and clears its cookie.
+async fn end_session(db: &sqlx::PgPool, cookies: &Cookies) -> Result<()> {
+    if let Some(session_cookie) = cookies.get("session_id") {
+        let token = session_cookie.value();
+        sqlx::query!("DELETE FROM sessions WHERE token = $1", token)
+            .execute(db)
+            .await?;
+    }
+
+    cookies.remove(Cookie::from("session_id"));
+    Ok(())
+}
+
+pub async fn logout(cookies: Cookies, State(state): State<Arc<AppState>>) -> Result<Response> {
+    end_session(&state.db, &cookies).await?;
+    Ok(Redirect::to("/").into_response())
+}
+
+pub async fn api_logout(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode> {
+    end_session(&state.db, &cookies).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadCount {
+    pub count: i64,
+}
+
+/// Published posts the signed-in user hasn't seen, for a badge.
+pub async fn api_unread_count(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<UnreadCount>> {
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    let last_seen_at = db::last_seen_at(&state.db, user.id).await?;
+    let count = db::count_posts_since(&state.db, user.id, last_seen_at).await?;
+    Ok(Json(UnreadCount { count }))
+}
+
+pub async fn api_mark_all_read(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
+
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    db::mark_seen(&state.db, user.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn create_post_form(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let Some(current_user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let boards = db::visible_boards(&state.db, Some(current_user.id)).await?;
+
+    let template = CreatePostTemplate {
+        error: state
+            .post_limiter
+            .time_until_allowed(current_user.id)
+            .map(cooldown_message),
+        boards,
+        current_user: Some(current_user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+/// Counts a new post against its author's rate limit, or explains why it
+/// is refused. Drafts and replies count as well: were drafts exempt, a flood
+/// could be staged as drafts and then published one after another.
+fn throttle_posting(state: &AppState, user_id: Uuid) -> std::result::Result<(), String> {
+    state.post_limiter.try_record(user_id).map_err(|remaining| {
+        tracing::warn!("Throttling posts from user {}", user_id);
+        cooldown_message(remaining)
+    })
+}
+
+/// Field errors for a post's title and body. `None` skips a field, as when
+/// an update leaves it unchanged.
+fn post_field_errors(title: Option<&str>, content: Option<&str>) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    if let Some(Err(reason)) = title.map(validate_post_title) {
+        fields.push(("title".to_string(), reason));
+    }
+    if let Some(Err(reason)) = content.map(validate_post_content) {
+        fields.push(("content".to_string(), reason));
+    }
+    fields
+}
+
+/// Checks the new-post form, returning its parsed tags and board.
+fn validate_post_form(
+    payload: &CreatePostPayload,
+    boards: &[Board],
+) -> Result<(Vec<String>, Option<Uuid>)> {
+    let mut fields = Vec::new();
+    if payload.title.trim().is_empty() {
+        fields.push(("title".to_string(), "Title cannot be empty".to_string()));
+    }
+    if payload.content.trim().is_empty() {
+        fields.push(("content".to_string(), "Content cannot be empty".to_string()));
+    }
+    fields.extend(post_field_errors(
+        Some(&payload.title),
+        Some(&payload.content),
+    ));
+
+    let tags = parse_tags(payload.tags.as_deref().unwrap_or("")).unwrap_or_else(|reason| {
+        fields.push(("tags".to_string(), reason));
+        Vec::new()
+    });
+
+    let board_slug = payload
+        .board_slug
+        .as_deref()
+        .map(str::trim)
+        .filter(|slug| !slug.is_empty());
+    let board_id = board_slug.and_then(|slug| {
+        let board = boards.iter().find(|b| b.slug == slug);
+        if board.is_none() {
+            fields.push((
+                "board_slug".to_string(),
+                "Selected board does not exist".to_string(),
+            ));
+        }
+        board.map(|b| b.id)
+    });
+
+    Error::validation(fields)?;
+    Ok((tags, board_id))
+}
+
+pub async fn create_post_submit(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    Form(payload): Form<CreatePostPayload>,
+) -> Result<Response> {
+    let user = check_auth(&cookies, &state).await;
+
+    let user = match user {
+        Some(u) => u,
+        None => {
+            return Ok(Redirect::to("/login").into_response());
+        }
+    };
+
+    // Private boards the user isn't a member of can't be picked.
+    let boards = db::visible_boards(&state.db, Some(user.id)).await?;
+
+    let (tags, board_id) = match form_error(validate_post_form(&payload, &boards))? {
+        Ok(valid) => valid,
+        Err(message) => {
+            let template = CreatePostTemplate {
+                error: Some(message),
+                boards,
+                current_user: Some(user.username),
+                csrf_token: ensure_csrf_token(&cookies),
+            };
+            return Ok(Html(template.render()?).into_response());
+        }
+    };
+
+    if state.maintenance.is_enabled() {
+        let template = CreatePostTemplate {
+            error: Some(READ_ONLY_MESSAGE.to_string()),
+            boards,
+            current_user: Some(user.username),
+            csrf_token: ensure_csrf_token(&cookies),
+        };
+        return Ok((StatusCode::SERVICE_UNAVAILABLE, Html(template.render()?)).into_response());
+    }
+
+    if let Err(message) = throttle_posting(&state, user.id) {
+        let template = CreatePostTemplate {
+            error: Some(message),
+            boards,
+            current_user: Some(user.username),
+            csrf_token: ensure_csrf_token(&cookies),
+        };
+        return Ok((StatusCode::TOO_MANY_REQUESTS, Html(template.render()?)).into_response());
+    }
+
+    let published = payload.published.is_some();
+
+    let mut tx = state.db.begin().await?;
+    let mut post = sqlx::query_as!(
+        Post,
+        "INSERT INTO posts (title, content, author_id, board_id, published) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        &payload.title,
+        &payload.content,
+        user.id,
+        board_id,
+        published
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    post.slug = Some(db::assign_post_slug(&mut tx, post.id, &post.title).await?);
+    db::record_revision(&mut tx, &post, user.id).await?;
+    db::set_post_tags(&mut tx, post.id, &tags).await?;
+    tx.commit().await?;
+    state.notify_new_post(&post);
+
+    if published {
+        Ok(Redirect::to("/").into_response())
+    } else {
+        Ok(Redirect::to("/drafts").into_response())
+    }
+}
+
+/// Toggles the signed-in user's upvote on a published post.
+///
+/// The (post, user) primary key means repeated or concurrent votes can never
+/// count twice; a second vote simply removes the first.
+pub async fn vote_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    Form(payload): Form<VotePayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    let visible = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND published = true AND deleted_at IS NULL AND board_visible_to(board_id, $2)) as "exists!""#,
+        id,
+        user.id
+    )
+    .fetch_one(&state.db)
+    .await?;
+    if !visible {
+        return Err(Error::NotFound);
+    }
+
+    let removed = sqlx::query!(
+        "DELETE FROM post_votes WHERE post_id = $1 AND user_id = $2",
+        id,
+        user.id
+    )
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    if removed == 0 {
+        sqlx::query!(
+            "INSERT INTO post_votes (post_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            id,
+            user.id
+        )
+        .execute(&state.db)
+        .await?;
+    }
+
+    // Only follow local paths so the form can't be used as an open redirect.
+    let back = payload
+        .next
+        .filter(|next| next.starts_with('/') && !next.starts_with("//"))
+        .unwrap_or_else(|| format!("/posts/{}", id));
+    Ok(Redirect::to(&back).into_response())
+}
+
+/// How long a reporter must wait before reporting the same post again.
+const REPORT_DUPLICATE_WINDOW_HOURS: i32 = 24;
+
+/// Files an abuse report against a published post. Guests may report too, so
+/// reports are rate-limited per address, and repeats from the same reporter
+/// (the account when signed in, otherwise the address) are refused.
+pub async fn report_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    IdPath(id): IdPath,
+    Form(payload): Form<ReportPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let client_ip = client_ip(&headers, connect_info, state.trust_proxy);
+    if state.report_limiter.time_until_allowed(client_ip).is_some() {
+        tracing::warn!("Rejecting report from rate-limited address {}", client_ip);
+        return Err(Error::TooManyRequests(
+            "Too many reports from your address. Try again later.".to_string(),
+        ));
+    }
+
+    let user = check_auth(&cookies, &state).await;
+    let user_id = user.as_ref().map(|u| u.id);
+
+    let visible = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND published = true AND deleted_at IS NULL AND board_visible_to(board_id, $2)) as "exists!""#,
+        id,
+        user_id
+    )
+    .fetch_one(&state.db)
+    .await?;
+    if !visible {
+        return Err(Error::NotFound);
+    }
+
+    let reason = parse_report_reason(&payload.reason).map_err(Error::BadRequest)?;
+    let reporter_ip = client_ip.to_string();
+
+    let duplicate = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM reports
+            WHERE post_id = $1
+              AND created_at > NOW() - make_interval(hours => $2)
+              AND (reporter_user_id = $3 OR ($3::uuid IS NULL AND reporter_ip = $4))
+        ) as "exists!"
+        "#,
+        id,
+        REPORT_DUPLICATE_WINDOW_HOURS,
+        user_id,
+        reporter_ip
+    )
+    .fetch_one(&state.db)
+    .await?;
+    if duplicate {
+        return Err(Error::Conflict(
+            "You have already reported this post".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        "INSERT INTO reports (post_id, reporter_user_id, reporter_ip, reason) VALUES ($1, $2, $3, $4)",
+        id,
+        user_id,
+        reporter_ip,
+        reason
+    )
+    .execute(&state.db)
+    .await?;
+    state.report_limiter.record(client_ip);
+
+    tracing::info!("Post {} reported from {}", id, reporter_ip);
+
+    Ok(Redirect::to(&format!("/posts/{}?reported=1", id)).into_response())
+}
+
+pub async fn list_drafts(cookies: Cookies, State(state): State<Arc<AppState>>) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let drafts = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE published = false AND author_id = $1 AND deleted_at IS NULL ORDER BY updated_at DESC",
+        user.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let template = DraftsTemplate {
+        drafts,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn publish_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let user = match check_auth(&cookies, &state).await {
+        Some(u) => u,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let post = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if post.author_id != user.id {
+        return Err(Error::Unauthorized);
+    }
+
+    let published = db::update_post(&state.db, id, None, None, Some(true), None, user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if !post.published {
+        state.notify_new_post(&published);
+    }
+
+    Ok(Redirect::to(&format!("/posts/{}", id)).into_response())
+}
+
+pub async fn edit_post_form(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<Response> {
+    let user = match check_auth(&cookies, &state).await {
+        Some(u) => u,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let post = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if post.author_id != user.id {
+        return Err(Error::Unauthorized);
+    }
+
+    let tags = db::post_tags(&state.db, id).await?.join(", ");
+    let template = EditPostTemplate {
+        post,
+        tags,
+        error: None,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+/// Loads a live post's history, if `user` is its author or an admin.
+async fn post_history(
+    state: &AppState,
+    user: &User,
+    id: Uuid,
+) -> Result<(Post, Vec<PostRevision>)> {
+    let post = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if post.author_id != user.id && !user.is_admin {
+        return Err(Error::Forbidden(
+            "Only the author can see this post's history".to_string(),
+        ));
+    }
+
+    let revisions = db::list_post_revisions(&state.db, id).await?;
+    Ok((post, revisions))
+}
+
+pub async fn post_history_page(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<Response> {
+    let user = match check_auth(&cookies, &state).await {
+        Some(u) => u,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let (post, revisions) = post_history(&state, &user, id).await?;
+    let template = PostHistoryTemplate {
+        post,
+        revisions,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn post_revision_page(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    RevisionPath(id, rev): RevisionPath,
+) -> Result<Response> {
+    let user = match check_auth(&cookies, &state).await {
+        Some(u) => u,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let (post, revisions) = post_history(&state, &user, id).await?;
+    // Newest first, so the one before `rev` is the next entry.
+    let index = revisions
+        .iter()
+        .position(|r| r.revision == rev)
+        .ok_or(Error::NotFound)?;
+    let older = revisions.get(index + 1);
+    let (old_title, old_content) = older.map_or(("", ""), |r| (&r.title, &r.content));
+    let revision = &revisions[index];
+
+    let template = PostRevisionTemplate {
+        title_diff: diff_lines(old_title, &revision.title),
+        content_diff: diff_lines(old_content, &revision.content),
+        previous: older.map(|r| r.revision),
+        revision: revision.clone(),
+        post,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn edit_post_submit(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    Form(payload): Form<EditPostPayload>,
+) -> Result<Response> {
+    let user = match check_auth(&cookies, &state).await {
+        Some(u) => u,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let post = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if post.author_id != user.id {
+        return Err(Error::Unauthorized);
+    }
+
+    // A form without the field (e.g. an older cached page) leaves tags alone.
+    let tags = payload.tags.as_deref().map(parse_tags).transpose();
+
+    let error = if payload.title.trim().is_empty() {
+        Some("Title cannot be empty".to_string())
+    } else if payload.content.trim().is_empty() {
+        Some("Content cannot be empty".to_string())
+    } else {
+        validate_post_title(&payload.title)
+            .and_then(|_| validate_post_content(&payload.content))
+            .and_then(|_| tags.as_ref().map(|_| ()).map_err(String::clone))
+            .err()
+    };
+
+    if let Some(error) = error {
+        let template = EditPostTemplate {
+            post,
+            tags: payload.tags.unwrap_or_default(),
+            error: Some(error),
+            current_user: Some(user.username),
+            csrf_token: ensure_csrf_token(&cookies),
+        };
+        return Ok(Html(template.render()?).into_response());
+    }
+
+    if state.maintenance.is_enabled() {
+        let template = EditPostTemplate {
+            post,
+            tags: payload.tags.unwrap_or_default(),
+            error: Some(READ_ONLY_MESSAGE.to_string()),
+            current_user: Some(user.username),
+            csrf_token: ensure_csrf_token(&cookies),
+        };
+        return Ok((StatusCode::SERVICE_UNAVAILABLE, Html(template.render()?)).into_response());
+    }
+
+    let published = payload.published.is_some();
+
+    let updated = db::update_post(
+        &state.db,
+        id,
+        Some(&payload.title),
+        Some(&payload.content),
+        Some(published),
+        payload.updated_at,
+        user.id,
+    )
+    .await;
+    let post = match updated {
+        Ok(post) => post.ok_or(Error::NotFound)?,
+        Err(Error::Conflict(msg)) => {
+            // Show the version that won so the author can redo their edit on top.
+            let post = sqlx::query_as!(
+                Post,
+                "SELECT * FROM posts WHERE id = $1 AND deleted_at IS NULL",
+                id
+            )
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or(Error::NotFound)?;
+            let template = EditPostTemplate {
+                tags: db::post_tags(&state.db, post.id).await?.join(", "),
+                post,
+                error: Some(msg),
+                current_user: Some(user.username),
+                csrf_token: ensure_csrf_token(&cookies),
+            };
+            return Ok((StatusCode::CONFLICT, Html(template.render()?)).into_response());
+        }
+        Err(e) => return Err(e),
+    };
+    if let Ok(Some(tags)) = tags {
+        let mut tx = state.db.begin().await?;
+        db::set_post_tags(&mut tx, post.id, &tags).await?;
+        tx.commit().await?;
+    }
+
+    if post.published {
+        Ok(Redirect::to(&format!("/posts/{}", post.id)).into_response())
+    } else {
+        Ok(Redirect::to("/drafts").into_response())
+    }
+}
+
+pub async fn create_reply(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    Form(payload): Form<ReplyPayload>,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let user = match check_auth(&cookies, &state).await {
+        Some(u) => u,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let parent = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE id = $1 AND published = true AND deleted_at IS NULL AND board_visible_to(board_id, $2)",
+        id,
+        user.id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    if payload.content.trim().is_empty() {
+        return Ok(Redirect::to(&format!("/posts/{}", parent.id)).into_response());
+    }
+    if !post_field_errors(None, Some(&payload.content)).is_empty() {
+        return Ok(Redirect::to(&format!("/posts/{}?reply_rejected=1", parent.id)).into_response());
+    }
+
+    throttle_posting(&state, user.id).map_err(Error::TooManyRequests)?;
+
+    let title = format!("Re: {}", parent.title);
+
+    let mut tx = state.db.begin().await?;
+    let reply = sqlx::query_as!(
+        Post,
+        "INSERT INTO posts (title, content, author_id, board_id, parent_id, published) VALUES ($1, $2, $3, $4, $5, true) RETURNING *",
+        title,
+        payload.content,
+        user.id,
+        parent.board_id,
+        parent.id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    db::record_revision(&mut tx, &reply, user.id).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to(&format!("/posts/{}", parent.id)).into_response())
+}
+
+pub async fn api_list_posts(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<PostListResponse>> {
+    let viewer = check_auth(&cookies, &state).await.map(|u| u.id);
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query
+        .per_page
+        .unwrap_or(state.default_page_size)
+        .clamp(1, state.max_page_size);
+    let offset = (page - 1).saturating_mul(per_page);
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM posts WHERE published = true AND parent_id IS NULL AND deleted_at IS NULL AND board_visible_to(board_id, $1)"#,
+        viewer
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    let posts = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE published = true AND parent_id IS NULL AND deleted_at IS NULL AND board_visible_to(board_id, $3) ORDER BY pinned DESC, created_at DESC LIMIT $1 OFFSET $2",
+        per_page,
+        offset,
+        viewer
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(PostListResponse {
+        posts,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// How long the readiness probe waits for the database before giving up.
+const HEALTH_DB_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Readiness probe: healthy only while the database answers a trivial query.
+pub async fn health(State(state): State<Arc<AppState>>) -> (StatusCode, &'static str) {
+    let ping = sqlx::query("SELECT 1").execute(&state.db);
+    match tokio::time::timeout(HEALTH_DB_TIMEOUT, ping).await {
+        Ok(Ok(_)) => (StatusCode::OK, "OK"),
+        Ok(Err(e)) => {
+            tracing::warn!("Health check failed: {}", e);
+            (StatusCode::SERVICE_UNAVAILABLE, "Database unreachable")
+        }
+        Err(_) => {
+            tracing::warn!("Health check timed out waiting for the database");
+            (StatusCode::SERVICE_UNAVAILABLE, "Database unreachable")
+        }
+    }
+}
+
+/// Liveness probe: answers as long as the process is serving requests.
+pub async fn health_live() -> (StatusCode, &'static str) {
+    (StatusCode::OK, "OK")
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostListResponse {
+    pub posts: Vec<Post>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+pub async fn api_get_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let viewer = check_auth(&cookies, &state).await.map(|u| u.id);
+    let post = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE id = $1 AND published = true AND deleted_at IS NULL AND board_visible_to(board_id, $2)",
+        id,
+        viewer
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    let body = serde_json::to_vec(&post).map_err(|e| Error::Internal(e.to_string()))?;
+    etag::respond(&headers, etag::strong(&body), || {
+        Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+    })
+}
+
+/// A post's direct replies, oldest first, paged like `api_list_posts`.
+pub async fn api_list_replies(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<PostListResponse>> {
+    let viewer = check_auth(&cookies, &state).await.map(|u| u.id);
+    let parent_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND published = true AND deleted_at IS NULL AND board_visible_to(board_id, $2)) as "exists!""#,
+        id,
+        viewer
+    )
+    .fetch_one(&state.db)
+    .await?;
+    if !parent_exists {
+        return Err(Error::NotFound);
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query
+        .per_page
+        .unwrap_or(state.default_page_size)
+        .clamp(1, state.max_page_size);
+    let offset = (page - 1).saturating_mul(per_page);
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM posts WHERE parent_id = $1 AND published = true AND deleted_at IS NULL"#,
+        id
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    let posts = sqlx::query_as!(
+        Post,
+        "SELECT * FROM posts WHERE parent_id = $1 AND published = true AND deleted_at IS NULL ORDER BY created_at ASC, id ASC LIMIT $2 OFFSET $3",
+        id,
+        per_page,
+        offset
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(PostListResponse {
+        posts,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePostRequest {
+    pub title: String,
+    pub content: String,
+    pub author_id: Uuid,
+    pub published: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdatePostRequest {
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub published: Option<bool>,
+    /// The post's `updated_at` as the client last saw it. When set, the
+    /// update is refused with 409 if the post has changed since.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Header a JSON client sets so that retrying a create can't post twice.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Reads the optional `Idempotency-Key` header: 1-255 visible ASCII characters.
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .ok()
+        .filter(|key| {
+            !key.is_empty()
+                && key.len() <= MAX_IDEMPOTENCY_KEY_LEN
+                && key.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .ok_or_else(|| {
+            Error::BadRequest(format!(
+                "Idempotency-Key must be 1-{} visible ASCII characters",
+                MAX_IDEMPOTENCY_KEY_LEN
+            ))
+        })?;
+    Ok(Some(key.to_string()))
+}
+
+/// Creates a post. With an `Idempotency-Key` header, repeating the request
+/// within a day returns the post the first one created, with `200 OK`.
+pub async fn create_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<CreatePostRequest>,
+) -> Result<(StatusCode, Json<Post>)> {
+    state.maintenance.check()?;
+
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    if user.id != payload.author_id {
+        return Err(Error::Unauthorized);
+    }
+
+    let key = idempotency_key(&headers)?;
+    if let Some(key) = &key {
+        // A retry is answered before validation and throttling, which the
+        // original request already passed.
+        if let Some(post) = db::post_for_idempotency_key(&state.db, user.id, key).await? {
+            return Ok((StatusCode::OK, Json(post)));
+        }
+    }
+
+    Error::validation(post_field_errors(
+        Some(&payload.title),
+        Some(&payload.content),
+    ))?;
+    throttle_posting(&state, user.id).map_err(Error::TooManyRequests)?;
+
+    if let Some(key) = &key {
+        let created = db::create_post_with_idempotency_key(
+            &state.db,
+            user.id,
+            key,
+            &payload.title,
+            &payload.content,
+            payload.published.unwrap_or(false),
         )
-        .into_response());
+        .await?;
+        return match created {
+            Some(post) => {
+                state.notify_new_post(&post);
+                Ok((StatusCode::CREATED, Json(post)))
+            }
+            // A concurrent request with the same key got there first.
+            None => {
+                let post = db::post_for_idempotency_key(&state.db, user.id, key)
+                    .await?
+                    .ok_or_else(|| {
+                        Error::Conflict("Idempotency-Key is already in use".to_string())
+                    })?;
+                Ok((StatusCode::OK, Json(post)))
+            }
+        };
+    }
+
+    let mut tx = state.db.begin().await?;
+    let mut post = sqlx::query_as!(
+        Post,
+        "INSERT INTO posts (title, content, author_id, published) VALUES ($1, $2, $3, $4) RETURNING *",
+        &payload.title,
+        &payload.content,
+        payload.author_id,
+        payload.published.unwrap_or(false)
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    post.slug = Some(db::assign_post_slug(&mut tx, post.id, &post.title).await?);
+    db::record_revision(&mut tx, &post, payload.author_id).await?;
+    tx.commit().await?;
+    state.notify_new_post(&post);
+
+    Ok((StatusCode::CREATED, Json(post)))
+}
+
+pub async fn ws_posts(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
+    let events = state.post_events.subscribe();
+    let db = state.db.clone();
+    ws.on_upgrade(move |socket| stream_new_posts(socket, db, events))
+}
+
+/// Forwards each newly published post to the client as a JSON text frame
+/// until either side goes away.
+/// The stream is anonymous, so posts in private boards are never sent.
+async fn stream_new_posts(
+    mut socket: WebSocket,
+    db: sqlx::PgPool,
+    mut events: broadcast::Receiver<Post>,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(post) => {
+                    match db::can_use_board(&db, post.board_id, None).await {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(e) => {
+                            tracing::error!("Failed to check board of post {}: {}", post.id, e);
+                            continue;
+                        }
+                    }
+                    let json = match serde_json::to_string(&post) {
+                        Ok(json) => json,
+                        Err(e) => {
+                            tracing::error!("Failed to serialize post {}: {}", post.id, e);
+                            continue;
+                        }
+                    };
+                    if socket.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::debug!("WebSocket client lagged, dropped {} posts", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+pub async fn update_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    Json(payload): Json<UpdatePostRequest>,
+) -> Result<Json<Post>> {
+    state.maintenance.check()?;
+
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    let existing_post = sqlx::query!("SELECT author_id FROM posts WHERE id = $1", id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if user.id != existing_post.author_id {
+        return Err(Error::Unauthorized);
+    }
+
+    Error::validation(post_field_errors(
+        payload.title.as_deref(),
+        payload.content.as_deref(),
+    ))?;
+
+    let post = db::update_post(
+        &state.db,
+        id,
+        payload.title.as_deref(),
+        payload.content.as_deref(),
+        payload.published,
+        payload.updated_at,
+        user.id,
+    )
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    Ok(Json(post))
+}
+
+pub async fn delete_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
+
+    let user = check_auth(&cookies, &state)
+        .await
+        .ok_or(Error::Unauthorized)?;
+
+    let existing_post = sqlx::query!("SELECT author_id FROM posts WHERE id = $1", id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if user.id != existing_post.author_id {
+        return Err(Error::Unauthorized);
+    }
+
+    let result =
+        sqlx::query("UPDATE posts SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .execute(&state.db)
+            .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn restore_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
+
+    let user = require_admin(&cookies, &state).await?;
+
+    let result =
+        sqlx::query("UPDATE posts SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL")
+            .bind(id)
+            .execute(&state.db)
+            .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    tracing::info!("Post {} restored by {}", id, user.username);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn admin_auth_log(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+    if !user.is_admin {
+        return Err(Error::Unauthorized);
+    }
+
+    let events = sqlx::query_as!(
+        AuthEvent,
+        "SELECT id, user_id, username, event_type, ip, user_agent, created_at FROM auth_events ORDER BY created_at DESC LIMIT 100"
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let template = AuthLogTemplate {
+        events,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn admin_delete_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
+
+    let admin = require_admin(&cookies, &state).await?;
+
+    let result =
+        sqlx::query("UPDATE posts SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .execute(&state.db)
+            .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    tracing::info!("Post {} deleted by admin {}", id, admin.username);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn admin_unpublish_post(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<Json<Post>> {
+    state.maintenance.check()?;
+
+    let admin = require_admin(&cookies, &state).await?;
+
+    let post = db::update_post(&state.db, id, None, None, Some(false), None, admin.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    tracing::info!("Post {} unpublished by admin {}", id, admin.username);
+
+    Ok(Json(post))
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+}
+
+/// Switches read-only maintenance mode on or off for the web and SSH front
+/// ends alike. Not subject to the mode itself, so it can always be undone.
+pub async fn admin_set_maintenance(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<MaintenancePayload>,
+) -> Result<Json<MaintenanceStatus>> {
+    let admin = require_admin(&cookies, &state).await?;
+
+    state.maintenance.set(payload.enabled);
+    tracing::warn!(
+        "Maintenance mode turned {} by admin {}",
+        if payload.enabled { "on" } else { "off" },
+        admin.username
+    );
+
+    Ok(Json(MaintenanceStatus {
+        enabled: payload.enabled,
+    }))
+}
+
+pub async fn admin_toggle_pin(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<Json<Post>> {
+    state.maintenance.check()?;
+
+    let admin = require_admin(&cookies, &state).await?;
+
+    let post = sqlx::query_as!(
+        Post,
+        "UPDATE posts SET pinned = NOT pinned WHERE id = $1 AND deleted_at IS NULL RETURNING *",
+        id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    tracing::info!(
+        "Post {} {} by admin {}",
+        id,
+        if post.pinned { "pinned" } else { "unpinned" },
+        admin.username
+    );
+
+    Ok(Json(post))
+}
+
+pub async fn admin_reports(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response> {
+    let Some(user) = check_auth(&cookies, &state).await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+    if !user.is_admin {
+        return Err(Error::Unauthorized);
+    }
+
+    let reports = sqlx::query_as!(
+        ReportWithPost,
+        r#"
+        SELECT r.id, r.post_id, p.title as post_title, u.username as "reporter_username?",
+            r.reporter_ip, r.reason, r.created_at
+        FROM reports r
+        JOIN posts p ON r.post_id = p.id
+        LEFT JOIN users u ON r.reporter_user_id = u.id
+        WHERE NOT r.resolved
+        ORDER BY r.created_at DESC
+        LIMIT 100
+        "#
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let template = ReportsTemplate {
+        reports,
+        current_user: Some(user.username),
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn admin_resolve_report(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<Response> {
+    state.maintenance.check()?;
+
+    let admin = require_admin(&cookies, &state).await?;
+
+    let result = sqlx::query!(
+        "UPDATE reports SET resolved = true WHERE id = $1 AND NOT resolved",
+        id
+    )
+    .execute(&state.db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound);
     }
 
-    let password_hash = AuthService::hash_password(&payload.password)?;
+    tracing::info!("Report {} resolved by admin {}", id, admin.username);
+
+    Ok(Redirect::to("/admin/reports").into_response())
+}
+
+#[derive(Template)]
+#[template(path = "boards.html")]
+struct BoardsTemplate {
+    boards: Vec<Board>,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "board_posts.html")]
+struct BoardPostsTemplate {
+    board: Board,
+    posts: Vec<PostWithAuthor>,
+    page: i64,
+    total_pages: i64,
+    avatars: AvatarProvider,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+/// An online user with their connection time already formatted.
+struct OnlineView {
+    user: OnlineUser,
+    connected_for: String,
+}
+
+#[derive(Template)]
+#[template(path = "online.html")]
+struct OnlineTemplate {
+    users: Vec<OnlineView>,
+    web_window_minutes: i64,
+    current_user: Option<String>,
+    csrf_token: String,
+}
+
+pub async fn online_users(
+    State(state): State<Arc<AppState>>,
+    cookies: Cookies,
+) -> Result<Response> {
+    let current_user = check_auth(&cookies, &state).await.map(|u| u.username);
+
+    let now = Utc::now();
+    let users = state
+        .presence
+        .online_users(&state.db)
+        .await?
+        .into_iter()
+        .map(|user| OnlineView {
+            connected_for: user.connected_for(now),
+            user,
+        })
+        .collect();
+
+    let template = OnlineTemplate {
+        users,
+        web_window_minutes: WEB_ONLINE_WINDOW_MINUTES,
+        current_user,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn list_boards(State(state): State<Arc<AppState>>, cookies: Cookies) -> Result<Response> {
+    let user = check_auth(&cookies, &state).await;
+    let boards = db::visible_boards(&state.db, user.as_ref().map(|u| u.id)).await?;
+
+    let current_user = user.map(|u| u.username);
+
+    let template = BoardsTemplate {
+        boards,
+        current_user,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+pub async fn board_posts(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+    Query(query): Query<PageQuery>,
+    cookies: Cookies,
+) -> Result<Response> {
+    let user = check_auth(&cookies, &state).await;
+
+    // A private board looks missing to non-members rather than forbidden.
+    let board = sqlx::query_as::<_, Board>(
+        "SELECT * FROM boards WHERE slug = $1 AND board_visible_to(id, $2)",
+    )
+    .bind(&slug)
+    .bind(user.as_ref().map(|u| u.id))
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = state.default_page_size;
+    let offset = (page - 1).saturating_mul(per_page);
 
-    let user = sqlx::query_as!(
-        User,
-        "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING id, username, email, password_hash, created_at, last_login_ip, last_login_at",
-        payload.username,
-        payload.email,
-        password_hash
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM posts WHERE board_id = $1 AND published = true AND parent_id IS NULL AND deleted_at IS NULL"#,
+        board.id
     )
     .fetch_one(&state.db)
     .await?;
 
-    let token = AuthService::generate_session_token();
-    let expires_at = Utc::now() + Duration::days(7);
-
-    sqlx::query!(
-        "INSERT INTO sessions (user_id, token, expires_at) VALUES ($1, $2, $3)",
-        user.id,
-        token,
-        expires_at
+    let posts = sqlx::query_as!(
+        PostWithAuthor,
+        r#"
+        SELECT 
+            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published, p.pinned,
+            p.board_id, b.name as "board_name?", b.slug as "board_slug?", p.parent_id,
+            u.username as author_username, u.display_name as author_display_name, u.email as author_email,
+            (SELECT COUNT(*) FROM post_votes v WHERE v.post_id = p.id) as "vote_count!"
+        FROM posts p
+        JOIN users u ON p.author_id = u.id
+        LEFT JOIN boards b ON p.board_id = b.id
+        WHERE p.board_id = $1 AND p.published = true AND p.parent_id IS NULL AND p.deleted_at IS NULL
+        ORDER BY p.created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        board.id,
+        per_page,
+        offset
     )
-    .execute(&state.db)
+    .fetch_all(&state.db)
     .await?;
 
-    let mut cookie = Cookie::new("session_id", token);
-    cookie.set_path("/");
-    cookie.set_http_only(true);
-    cookies.add(cookie);
+    let current_user = user.map(|u| u.username);
+    let total_pages = ((total + per_page - 1) / per_page).max(1);
 
-    Ok(Redirect::to("/").into_response())
+    let template = BoardPostsTemplate {
+        board,
+        posts,
+        page,
+        total_pages,
+        avatars: state.avatars,
+        current_user,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
 }
 
-pub async fn logout(cookies: Cookies, State(state): State<Arc<AppState>>) -> Result<Response> {
-    if let Some(session_cookie) = cookies.get("session_id") {
-        let token = session_cookie.value();
-        sqlx::query!("DELETE FROM sessions WHERE token = $1", token)
-            .execute(&state.db)
-            .await?;
-    }
-
-    cookies.remove(Cookie::from("session_id"));
-    Ok(Redirect::to("/").into_response())
+#[derive(Template)]
+#[template(path = "tag_posts.html")]
+struct TagPostsTemplate {
+    tag: String,
+    posts: Vec<PostWithAuthor>,
+    page: i64,
+    total_pages: i64,
+    avatars: AvatarProvider,
+    current_user: Option<String>,
+    csrf_token: String,
 }
 
-pub async fn create_post_form(
-    cookies: Cookies,
+pub async fn tag_posts(
     State(state): State<Arc<AppState>>,
-) -> Result<Response> {
-    let current_user = check_auth(&cookies, &state.db).await;
-
-    if current_user.is_none() {
-        return Ok(Redirect::to("/login").into_response());
-    }
-
-    let template = CreatePostTemplate {
-        error: None,
-        current_user: current_user.map(|u| u.username),
-    };
-    Ok(Html(
-        template
-            .render()
-            .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-    )
-    .into_response())
-}
-
-pub async fn create_post_submit(
+    Path(tag): Path<String>,
+    Query(query): Query<PageQuery>,
     cookies: Cookies,
-    State(state): State<Arc<AppState>>,
-    Form(payload): Form<CreatePostPayload>,
 ) -> Result<Response> {
-    let user = check_auth(&cookies, &state.db).await;
-
-    let user = match user {
-        Some(u) => u,
-        None => {
-            return Ok(Redirect::to("/login").into_response());
-        }
+    // Normalizing lets /tags/Rust find posts tagged "rust".
+    let tag = match parse_tags(&tag).map(|tags| tags.into_iter().next()) {
+        Ok(Some(tag)) => tag,
+        _ => return Err(Error::NotFound),
     };
 
-    if payload.title.trim().is_empty() {
-        let template = CreatePostTemplate {
-            error: Some("Title cannot be empty".to_string()),
-            current_user: Some(user.username),
-        };
-        return Ok(Html(
-            template
-                .render()
-                .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-        )
-        .into_response());
-    }
-
-    if payload.content.trim().is_empty() {
-        let template = CreatePostTemplate {
-            error: Some("Content cannot be empty".to_string()),
-            current_user: Some(user.username),
-        };
-        return Ok(Html(
-            template
-                .render()
-                .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-        )
-        .into_response());
-    }
-
-    let published = payload.published.is_some();
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = state.default_page_size;
+    let offset = (page - 1).saturating_mul(per_page);
+    let user = check_auth(&cookies, &state).await;
+    let user_id = user.as_ref().map(|u| u.id);
 
-    sqlx::query!(
-        "INSERT INTO posts (title, content, author_id, published) VALUES ($1, $2, $3, $4)",
-        payload.title,
-        payload.content,
-        user.id,
-        published
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM posts p
+        JOIN post_tags pt ON pt.post_id = p.id
+        JOIN tags t ON pt.tag_id = t.id
+        WHERE t.name = $1 AND p.published = true AND p.parent_id IS NULL AND p.deleted_at IS NULL
+            AND board_visible_to(p.board_id, $2)
+        "#,
+        tag,
+        user_id
     )
-    .execute(&state.db)
+    .fetch_one(&state.db)
     .await?;
 
-    Ok(Redirect::to("/").into_response())
-}
-
-pub async fn api_list_posts(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Post>>> {
-    let posts = sqlx::query_as::<_, Post>(
-        "SELECT * FROM posts WHERE published = true ORDER BY created_at DESC",
+    let posts = sqlx::query_as!(
+        PostWithAuthor,
+        r#"
+        SELECT 
+            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published, p.pinned,
+            p.board_id, b.name as "board_name?", b.slug as "board_slug?", p.parent_id,
+            u.username as author_username, u.display_name as author_display_name, u.email as author_email,
+            (SELECT COUNT(*) FROM post_votes v WHERE v.post_id = p.id) as "vote_count!"
+        FROM posts p
+        JOIN users u ON p.author_id = u.id
+        LEFT JOIN boards b ON p.board_id = b.id
+        JOIN post_tags pt ON pt.post_id = p.id
+        JOIN tags t ON pt.tag_id = t.id
+        WHERE t.name = $1 AND p.published = true AND p.parent_id IS NULL AND p.deleted_at IS NULL
+            AND board_visible_to(p.board_id, $4)
+        ORDER BY p.created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        tag,
+        per_page,
+        offset,
+        user_id
     )
     .fetch_all(&state.db)
     .await?;
 
-    Ok(Json(posts))
-}
+    let current_user = user.map(|u| u.username);
+    let total_pages = ((total + per_page - 1) / per_page).max(1);
 
-pub async fn health() -> (StatusCode, &'static str) {
-    (StatusCode::OK, "OK")
+    let template = TagPostsTemplate {
+        tag,
+        posts,
+        page,
+        total_pages,
+        avatars: state.avatars,
+        current_user,
+        csrf_token: ensure_csrf_token(&cookies),
+    };
+    Ok(Html(template.render()?).into_response())
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct CreatePostRequest {
-    pub title: String,
-    pub content: String,
-    pub author_id: Uuid,
-    pub published: Option<bool>,
+pub struct CreateBoardRequest {
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    /// Defaults to public.
+    pub visibility: Option<BoardVisibility>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct UpdatePostRequest {
-    pub title: Option<String>,
-    pub content: Option<String>,
-    pub published: Option<bool>,
+pub struct UpdateBoardRequest {
+    pub name: Option<String>,
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    pub visibility: Option<BoardVisibility>,
 }
 
-pub async fn create_post(
-    cookies: Cookies,
-    State(state): State<Arc<AppState>>,
-    Json(payload): Json<CreatePostRequest>,
-) -> Result<(StatusCode, Json<Post>)> {
-    let user = check_auth(&cookies, &state.db)
-        .await
-        .ok_or(Error::Unauthorized)?;
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BoardMemberRequest {
+    pub username: String,
+}
 
-    if user.id != payload.author_id {
-        return Err(Error::Unauthorized);
+/// Turns a unique-constraint violation on `boards` into a 409 naming the field.
+fn board_conflict(e: sqlx::Error) -> Error {
+    let constraint = e
+        .as_database_error()
+        .filter(|db| db.is_unique_violation())
+        .and_then(|db| db.constraint().map(str::to_string));
+    match constraint.as_deref() {
+        Some("boards_slug_key") => Error::Conflict("A board with this slug already exists".into()),
+        Some("boards_name_key") => Error::Conflict("A board with this name already exists".into()),
+        _ => Error::Database(e),
     }
+}
 
-    let post = sqlx::query_as::<_, Post>(
-        "INSERT INTO posts (title, content, author_id, published) VALUES ($1, $2, $3, $4) RETURNING *"
-    )
-    .bind(&payload.title)
-    .bind(&payload.content)
-    .bind(payload.author_id)
-    .bind(payload.published.unwrap_or(false))
-    .fetch_one(&state.db)
-    .await?;
+pub async fn api_list_boards(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Board>>> {
+    let viewer = check_auth(&cookies, &state).await.map(|u| u.id);
+    let boards = db::visible_boards(&state.db, viewer).await?;
 
-    Ok((StatusCode::CREATED, Json(post)))
+    Ok(Json(boards))
 }
 
-pub async fn update_post(
+pub async fn api_create_board(
     cookies: Cookies,
     State(state): State<Arc<AppState>>,
-    Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePostRequest>,
-) -> Result<Json<Post>> {
-    let user = check_auth(&cookies, &state.db)
-        .await
-        .ok_or(Error::Unauthorized)?;
+    Json(payload): Json<CreateBoardRequest>,
+) -> Result<(StatusCode, Json<Board>)> {
+    state.maintenance.check()?;
 
-    let existing_post = sqlx::query!("SELECT author_id FROM posts WHERE id = $1", id)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let admin = require_admin(&cookies, &state).await?;
 
-    if user.id != existing_post.author_id {
-        return Err(Error::Unauthorized);
-    }
+    validate_board_name(&payload.name)
+        .and_then(|_| validate_board_slug(&payload.slug))
+        .map_err(Error::BadRequest)?;
+
+    let board = sqlx::query_as::<_, Board>(
+        "INSERT INTO boards (name, slug, description, visibility) VALUES ($1, $2, $3, $4) RETURNING *",
+    )
+    .bind(payload.name.trim())
+    .bind(&payload.slug)
+    .bind(&payload.description)
+    .bind(
+        payload
+            .visibility
+            .unwrap_or(BoardVisibility::Public)
+            .as_str(),
+    )
+    .fetch_one(&state.db)
+    .await
+    .map_err(board_conflict)?;
 
-    let mut query = String::from("UPDATE posts SET updated_at = NOW()");
-    let mut bind_count = 1;
+    tracing::info!("Board {} created by admin {}", board.slug, admin.username);
 
-    if payload.title.is_some() {
-        query.push_str(&format!(", title = ${}", bind_count));
-        bind_count += 1;
-    }
-    if payload.content.is_some() {
-        query.push_str(&format!(", content = ${}", bind_count));
-        bind_count += 1;
-    }
-    if payload.published.is_some() {
-        query.push_str(&format!(", published = ${}", bind_count));
-        bind_count += 1;
-    }
+    Ok((StatusCode::CREATED, Json(board)))
+}
 
-    query.push_str(&format!(" WHERE id = ${} RETURNING *", bind_count));
+pub async fn api_update_board(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    Json(payload): Json<UpdateBoardRequest>,
+) -> Result<Json<Board>> {
+    state.maintenance.check()?;
 
-    let mut q = sqlx::query_as::<_, Post>(&query);
+    let admin = require_admin(&cookies, &state).await?;
 
-    if let Some(title) = &payload.title {
-        q = q.bind(title);
-    }
-    if let Some(content) = &payload.content {
-        q = q.bind(content);
+    if let Some(name) = &payload.name {
+        validate_board_name(name).map_err(Error::BadRequest)?;
     }
-    if let Some(published) = payload.published {
-        q = q.bind(published);
+    if let Some(slug) = &payload.slug {
+        validate_board_slug(slug).map_err(Error::BadRequest)?;
     }
 
-    q = q.bind(id);
+    let board = sqlx::query_as::<_, Board>(
+        "UPDATE boards
+         SET name = COALESCE($2, name),
+             slug = COALESCE($3, slug),
+             description = COALESCE($4, description),
+             visibility = COALESCE($5, visibility),
+             updated_at = NOW()
+         WHERE id = $1
+         RETURNING *",
+    )
+    .bind(id)
+    .bind(payload.name.as_deref().map(str::trim))
+    .bind(&payload.slug)
+    .bind(&payload.description)
+    .bind(payload.visibility.map(|v| v.as_str()))
+    .fetch_optional(&state.db)
+    .await
+    .map_err(board_conflict)?
+    .ok_or(Error::NotFound)?;
 
-    let post = q.fetch_optional(&state.db).await?.ok_or(Error::NotFound)?;
+    tracing::info!("Board {} updated by admin {}", board.slug, admin.username);
 
-    Ok(Json(post))
+    Ok(Json(board))
 }
 
-pub async fn delete_post(
+/// Deletes a board. Its posts are kept and simply become unfiled: the
+/// `posts.board_id` foreign key is `ON DELETE SET NULL`, so nothing is lost
+/// and an admin can re-file them under another board later. Unfiled posts
+/// are public, so a private board has to be emptied first.
+pub async fn api_delete_board(
     cookies: Cookies,
     State(state): State<Arc<AppState>>,
-    Path(id): Path<Uuid>,
+    IdPath(id): IdPath,
 ) -> Result<StatusCode> {
-    let user = check_auth(&cookies, &state.db)
-        .await
-        .ok_or(Error::Unauthorized)?;
+    state.maintenance.check()?;
 
-    let existing_post = sqlx::query!("SELECT author_id FROM posts WHERE id = $1", id)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let admin = require_admin(&cookies, &state).await?;
 
-    if user.id != existing_post.author_id {
-        return Err(Error::Unauthorized);
+    let private_with_posts = sqlx::query_scalar!(
+        r#"SELECT EXISTS(
+            SELECT 1 FROM boards b JOIN posts p ON p.board_id = b.id
+            WHERE b.id = $1 AND b.visibility = 'private'
+        ) AS "exists!""#,
+        id
+    )
+    .fetch_one(&state.db)
+    .await?;
+    if private_with_posts {
+        return Err(Error::Conflict(
+            "Deleting this private board would make its posts public; move or delete them first"
+                .to_string(),
+        ));
     }
 
-    let result = sqlx::query("DELETE FROM posts WHERE id = $1")
-        .bind(id)
+    let result = sqlx::query!("DELETE FROM boards WHERE id = $1", id)
         .execute(&state.db)
         .await?;
 
@@ -535,83 +3515,116 @@ pub async fn delete_post(
         return Err(Error::NotFound);
     }
 
+    tracing::info!("Board {} deleted by admin {}", id, admin.username);
+
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(Template)]
-#[template(path = "boards.html")]
-struct BoardsTemplate {
-    boards: Vec<Board>,
-    current_user: Option<String>,
-}
+/// Usernames of a board's members, for admins managing a private board.
+pub async fn api_list_board_members(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+) -> Result<Json<Vec<String>>> {
+    require_admin(&cookies, &state).await?;
 
-#[derive(Template)]
-#[template(path = "board_posts.html")]
-struct BoardPostsTemplate {
-    board: Board,
-    posts: Vec<PostWithAuthor>,
-    current_user: Option<String>,
+    let board_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM boards WHERE id = $1) AS "exists!""#,
+        id
+    )
+    .fetch_one(&state.db)
+    .await?;
+    if !board_exists {
+        return Err(Error::NotFound);
+    }
+
+    let members = sqlx::query_scalar!(
+        "SELECT u.username FROM board_members m JOIN users u ON u.id = m.user_id
+         WHERE m.board_id = $1 ORDER BY u.username",
+        id
+    )
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(members))
 }
 
-pub async fn list_boards(State(state): State<Arc<AppState>>, cookies: Cookies) -> Result<Response> {
-    let boards = sqlx::query_as::<_, Board>("SELECT * FROM boards ORDER BY name ASC")
-        .fetch_all(&state.db)
-        .await?;
+pub async fn api_add_board_member(
+    cookies: Cookies,
+    State(state): State<Arc<AppState>>,
+    IdPath(id): IdPath,
+    Json(payload): Json<BoardMemberRequest>,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
 
-    let current_user = check_auth(&cookies, &state.db).await.map(|u| u.username);
+    let admin = require_admin(&cookies, &state).await?;
 
-    let template = BoardsTemplate {
-        boards,
-        current_user,
-    };
-    Ok(Html(
-        template
-            .render()
-            .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
+    let added = sqlx::query!(
+        "INSERT INTO board_members (board_id, user_id)
+         SELECT b.id, u.id FROM boards b, users u WHERE b.id = $1 AND u.username = $2
+         ON CONFLICT DO NOTHING",
+        id,
+        payload.username
     )
-    .into_response())
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    if added == 0 {
+        let known = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM board_members m JOIN users u ON u.id = m.user_id
+                WHERE m.board_id = $1 AND u.username = $2
+            ) AS "exists!""#,
+            id,
+            payload.username
+        )
+        .fetch_one(&state.db)
+        .await?;
+        // Already a member is fine; a missing board or user is not.
+        if !known {
+            return Err(Error::NotFound);
+        }
+    } else {
+        tracing::info!(
+            "{} added to board {} by admin {}",
+            payload.username,
+            id,
+            admin.username
+        );
+    }
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn get_board_posts(
-    State(state): State<Arc<AppState>>,
-    Path(slug): Path<String>,
+pub async fn api_remove_board_member(
     cookies: Cookies,
-) -> Result<Response> {
-    let board = sqlx::query_as::<_, Board>("SELECT * FROM boards WHERE slug = $1")
-        .bind(&slug)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    State(state): State<Arc<AppState>>,
+    Path((id, username)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    state.maintenance.check()?;
 
-    let posts = sqlx::query_as!(
-        PostWithAuthor,
-        r#"
-        SELECT 
-            p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at, p.published,
-            p.board_id, b.name as board_name, b.slug as board_slug,
-            u.username as author_username, u.email as author_email
-        FROM posts p
-        JOIN users u ON p.author_id = u.id
-        LEFT JOIN boards b ON p.board_id = b.id
-        WHERE p.board_id = $1 AND p.published = true
-        ORDER BY p.created_at DESC
-        "#,
-        board.id
+    let admin = require_admin(&cookies, &state).await?;
+    let id = id.parse::<Uuid>().map_err(|_| Error::NotFound)?;
+
+    let result = sqlx::query!(
+        "DELETE FROM board_members m USING users u
+         WHERE m.user_id = u.id AND m.board_id = $1 AND u.username = $2",
+        id,
+        username
     )
-    .fetch_all(&state.db)
+    .execute(&state.db)
     .await?;
 
-    let current_user = check_auth(&cookies, &state.db).await.map(|u| u.username);
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    tracing::info!(
+        "{} removed from board {} by admin {}",
+        username,
+        id,
+        admin.username
+    );
 
-    let template = BoardPostsTemplate {
-        board,
-        posts,
-        current_user,
-    };
-    Ok(Html(
-        template
-            .render()
-            .map_err(|e| Error::Internal(format!("Template error: {}", e)))?,
-    )
-    .into_response())
+    Ok(StatusCode::NO_CONTENT)
 }
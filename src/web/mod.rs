@@ -0,0 +1,395 @@
+mod error_page;
+mod extract;
+mod handlers;
+mod routes;
+
+#[cfg(test)]
+mod tests;
+
+use axum::{
+    http::{header, HeaderName, HeaderValue, Method},
+    Extension, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use uuid::Uuid;
+
+use crate::{
+    auth::PasswordParams, avatar::AvatarProvider, maintenance::Maintenance, models::Post,
+    pow::ChallengeStore, presence::Presence, ratelimit::RateLimiter,
+    security_headers::ContentSecurityPolicy,
+};
+
+/// How many new posts a `/ws` client may fall behind before it skips ahead.
+const POST_EVENTS_CAPACITY: usize = 64;
+
+/// Abuse reports one address may file per window before being locked out.
+const REPORTS_PER_WINDOW: usize = 5;
+const REPORT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Password reset requests one address may make per window. Each one can
+/// write a token and a log line, so they are capped like reports.
+const PASSWORD_RESETS_PER_WINDOW: usize = 5;
+const PASSWORD_RESET_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: PgPool,
+    pub login_limiter: Arc<RateLimiter>,
+    pub report_limiter: Arc<RateLimiter>,
+    pub password_reset_limiter: Arc<RateLimiter>,
+    /// New posts per user, drafts and replies included.
+    pub post_limiter: Arc<RateLimiter<Uuid>>,
+    /// Newly published posts, fanned out to WebSocket clients.
+    pub post_events: broadcast::Sender<Post>,
+    /// Proof-of-work challenges issued by the registration form.
+    pub registration_challenges: Arc<ChallengeStore>,
+    pub registration_pow_bits: u32,
+    pub sessions: SessionLifetimes,
+    pub avatars: AvatarProvider,
+    /// Connected SSH users, kept current by the SSH server.
+    pub presence: Arc<Presence>,
+    /// Cost of new password hashes, also the bar for rehashing on login.
+    pub password_params: PasswordParams,
+    /// Take client addresses from `X-Forwarded-For`, set by a reverse proxy.
+    pub trust_proxy: bool,
+    /// Posts per page of listings, and the API's `per_page` default.
+    pub default_page_size: i64,
+    /// Cap on the API's `per_page`.
+    pub max_page_size: i64,
+    /// Read-only switch, shared with the SSH server.
+    pub maintenance: Arc<Maintenance>,
+    /// Banner shown once to each new user on their first index view.
+    pub welcome_message: Option<String>,
+}
+
+/// Sign-ins without "remember me" end this long after sign-in at the latest.
+const SHORT_SESSION_HOURS: i64 = 12;
+
+/// How long web sessions stay valid. Activity pushes a session's expiry to
+/// `idle_timeout` from now, but never past `max_age` after sign-in, or
+/// `SHORT_SESSION_HOURS` for sessions that were not remembered.
+#[derive(Clone, Copy, Debug)]
+pub struct SessionLifetimes {
+    pub idle_timeout: chrono::Duration,
+    pub max_age: chrono::Duration,
+}
+
+impl Default for SessionLifetimes {
+    fn default() -> Self {
+        Self {
+            idle_timeout: chrono::Duration::days(7),
+            max_age: chrono::Duration::days(30),
+        }
+    }
+}
+
+impl SessionLifetimes {
+    /// Expiry for a session signed in at `created_at` and active at `now`.
+    pub fn expires_at(
+        &self,
+        created_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+        remember: bool,
+    ) -> DateTime<Utc> {
+        let max_age = if remember {
+            self.max_age
+        } else {
+            self.max_age
+                .min(chrono::Duration::hours(SHORT_SESSION_HOURS))
+        };
+        (now + self.idle_timeout).min(created_at + max_age)
+    }
+}
+
+/// Tunables for the web front end, filled in from `Config` by `main`.
+#[derive(Clone, Debug)]
+pub struct WebSettings {
+    pub registration_pow_bits: u32,
+    pub sessions: SessionLifetimes,
+    /// Posts one user may create per `post_window` before being throttled.
+    pub posts_per_window: usize,
+    pub post_window: Duration,
+    pub avatar_provider: AvatarProvider,
+    pub password_params: PasswordParams,
+    pub trust_proxy: bool,
+    pub default_page_size: i64,
+    pub max_page_size: i64,
+    /// Whether to start in read-only maintenance mode.
+    pub maintenance_mode: bool,
+    pub welcome_message: Option<String>,
+}
+
+/// Registration proof-of-work is reduced to the challenge check; everything
+/// else matches the `Config` defaults.
+impl Default for WebSettings {
+    fn default() -> Self {
+        Self {
+            registration_pow_bits: 0,
+            sessions: SessionLifetimes::default(),
+            posts_per_window: 5,
+            post_window: Duration::from_secs(60),
+            avatar_provider: AvatarProvider::default(),
+            password_params: PasswordParams::default(),
+            trust_proxy: false,
+            default_page_size: 25,
+            max_page_size: 100,
+            maintenance_mode: false,
+            welcome_message: None,
+        }
+    }
+}
+
+impl AppState {
+    pub fn new(db: PgPool) -> Arc<Self> {
+        Self::with_settings(db, WebSettings::default())
+    }
+
+    pub fn with_settings(db: PgPool, settings: WebSettings) -> Arc<Self> {
+        let (post_events, _) = broadcast::channel(POST_EVENTS_CAPACITY);
+        Arc::new(Self {
+            db,
+            login_limiter: Arc::new(RateLimiter::default()),
+            report_limiter: Arc::new(RateLimiter::new(
+                REPORTS_PER_WINDOW,
+                REPORT_WINDOW,
+                REPORT_WINDOW,
+            )),
+            password_reset_limiter: Arc::new(RateLimiter::new(
+                PASSWORD_RESETS_PER_WINDOW,
+                PASSWORD_RESET_WINDOW,
+                PASSWORD_RESET_WINDOW,
+            )),
+            // Hitting the limit blocks posting until a full window has passed.
+            post_limiter: Arc::new(RateLimiter::new(
+                settings.posts_per_window,
+                settings.post_window,
+                settings.post_window,
+            )),
+            post_events,
+            registration_challenges: Arc::new(ChallengeStore::default()),
+            registration_pow_bits: settings.registration_pow_bits,
+            sessions: settings.sessions,
+            avatars: settings.avatar_provider,
+            presence: Arc::new(Presence::default()),
+            password_params: settings.password_params,
+            trust_proxy: settings.trust_proxy,
+            default_page_size: settings.default_page_size,
+            max_page_size: settings.max_page_size,
+            maintenance: Arc::new(Maintenance::new(settings.maintenance_mode)),
+            welcome_message: settings.welcome_message,
+        })
+    }
+
+    /// Announces a published post. Never blocks; having no listeners is fine.
+    pub fn notify_new_post(&self, post: &Post) {
+        if post.published {
+            self.post_events.send(post.clone()).ok();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthPayload {
+    pub username: String,
+    pub password: String,
+    /// A ticked checkbox from the form, or a boolean from JSON clients.
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub remember: bool,
+}
+
+/// Reads a checkbox-style flag: any string value means "on" (browsers only
+/// send ticked boxes), while JSON booleans are taken at face value.
+fn deserialize_flag<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Present(serde::de::IgnoredAny),
+    }
+
+    Ok(match Option::<Flag>::deserialize(deserializer)? {
+        Some(Flag::Bool(on)) => on,
+        Some(Flag::Present(_)) => true,
+        None => false,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterPayload {
+    pub username: String,
+    pub email: String,
+    pub password: String,
+    /// Solution to the form's proof-of-work challenge, filled in by script.
+    pub pow_nonce: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordPayload {
+    pub current_password: String,
+    pub new_password: String,
+    pub confirm_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordPayload {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordPayload {
+    pub new_password: String,
+    pub confirm_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountSettingsPayload {
+    pub display_name: String,
+    pub bio: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddKeyPayload {
+    pub public_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePostPayload {
+    pub title: String,
+    pub content: String,
+    pub board_slug: Option<String>,
+    /// Comma-separated tags, see `models::parse_tags`.
+    pub tags: Option<String>,
+    pub published: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditPostPayload {
+    pub title: String,
+    pub content: String,
+    pub tags: Option<String>,
+    pub published: Option<String>,
+    /// The post's `updated_at` when the form was rendered.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteAccountPayload {
+    /// The current password, re-entered to confirm.
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyPayload {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VotePayload {
+    /// Page to return to after voting.
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaintenancePayload {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportPayload {
+    pub reason: String,
+}
+
+/// Query string of the post page; `reported` is set after filing a report
+/// and `reply_rejected` after a reply failed validation.
+#[derive(Debug, Deserialize)]
+pub struct PostQuery {
+    pub reported: Option<String>,
+    pub reply_rejected: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+}
+
+/// Builds the CORS policy from the configured origin allow-list.
+///
+/// `CorsLayer::permissive()` used to be applied here. It echoes back any
+/// origin, which together with our session cookies would let any site make
+/// authenticated API calls on a visitor's behalf. Only listed origins get
+/// CORS headers now; with an empty list no cross-origin access is granted
+/// and browsers fall back to the same-origin policy.
+pub(crate) fn cors_layer(allowed_origins: &[String]) -> crate::Result<CorsLayer> {
+    if allowed_origins.is_empty() {
+        return Ok(CorsLayer::new());
+    }
+
+    let origins = allowed_origins
+        .iter()
+        .map(|origin| {
+            HeaderValue::from_str(origin)
+                .map_err(|_| crate::Error::Internal(format!("Invalid CORS origin: {:?}", origin)))
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            HeaderName::from_static(crate::csrf::CSRF_HEADER),
+            HeaderName::from_static(crate::request_id::REQUEST_ID_HEADER),
+        ])
+        .expose_headers([HeaderName::from_static(
+            crate::request_id::REQUEST_ID_HEADER,
+        )])
+        .allow_credentials(true))
+}
+
+/// Serves the web app until `shutdown` resolves, then stops accepting
+/// connections and lets in-flight requests finish.
+pub async fn serve(
+    addr: String,
+    state: Arc<AppState>,
+    allowed_origins: &[String],
+    content_security_policy: Option<&str>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> crate::Result<()> {
+    let mut app = Router::new()
+        .merge(routes::create_routes(state))
+        .layer(TraceLayer::new_for_http())
+        .layer(cors_layer(allowed_origins)?);
+    if let Some(policy) = content_security_policy {
+        app = app.layer(Extension(ContentSecurityPolicy::parse(policy)?));
+    }
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    tracing::info!("Web server listening on {}", addr);
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown)
+    .await
+    .map_err(|e| crate::Error::Internal(e.to_string()))?;
+
+    Ok(())
+}
@@ -1,13 +1,16 @@
 use axum::{
-    routing::{get, post, put},
+    middleware,
+    routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
 use tower_cookies::CookieManagerLayer;
 
-use super::{handlers, AppState};
+use super::{error_page, handlers, AppState};
+use crate::{csrf, request_id, security_headers};
 
-pub fn create_routes() -> Router<Arc<AppState>> {
+/// The site's routes with their middleware, serving `state`.
+pub fn create_routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/", get(handlers::index))
         .route(
@@ -18,22 +21,116 @@ pub fn create_routes() -> Router<Arc<AppState>> {
             "/register",
             get(handlers::register_form).post(handlers::register_submit),
         )
+        .route("/verify/:token", get(handlers::verify_email))
+        .route(
+            "/forgot-password",
+            get(handlers::forgot_password_form).post(handlers::forgot_password_submit),
+        )
+        .route(
+            "/reset-password/:token",
+            get(handlers::reset_password_form).post(handlers::reset_password_submit),
+        )
+        .route("/security-alert", get(handlers::security_alert))
         .route("/logout", post(handlers::logout))
+        .route("/api/login", post(handlers::api_login))
+        .route("/api/logout", post(handlers::api_logout))
+        .route("/api/me", get(handlers::api_me))
+        .route("/api/unread", get(handlers::api_unread_count))
+        .route("/api/read-all", post(handlers::api_mark_all_read))
+        .route(
+            "/account",
+            get(handlers::account_settings_form)
+                .post(handlers::account_settings_submit)
+                .delete(handlers::delete_account),
+        )
+        .route(
+            "/account/password",
+            get(handlers::change_password_form).post(handlers::change_password_submit),
+        )
+        .route(
+            "/account/keys",
+            get(handlers::list_keys).post(handlers::add_key),
+        )
+        .route("/account/keys/:id", delete(handlers::delete_key))
+        .route("/account/export", get(handlers::export_account))
         .route(
             "/new",
             get(handlers::create_post_form).post(handlers::create_post_submit),
         )
         .route("/posts/:id", get(handlers::get_post))
+        .route("/p/:slug", get(handlers::get_post_by_slug))
+        .route(
+            "/posts/:id/edit",
+            get(handlers::edit_post_form).post(handlers::edit_post_submit),
+        )
+        .route("/posts/:id/history", get(handlers::post_history_page))
+        .route("/posts/:id/history/:rev", get(handlers::post_revision_page))
+        .route("/posts/:id/reply", post(handlers::create_reply))
+        .route("/posts/:id/publish", post(handlers::publish_post))
+        .route("/posts/:id/vote", post(handlers::vote_post))
+        .route("/posts/:id/report", post(handlers::report_post))
+        .route("/drafts", get(handlers::list_drafts))
+        .route("/search", get(handlers::search_posts))
+        .route("/feed.xml", get(handlers::rss_feed))
+        .route("/ws", get(handlers::ws_posts))
+        .route("/users/:username", get(handlers::user_profile))
+        .route("/avatar/:file", get(handlers::avatar))
+        .route("/online", get(handlers::online_users))
         .route("/boards", get(handlers::list_boards))
-        .route("/boards/:slug", get(handlers::get_board_posts))
+        .route("/boards/:slug", get(handlers::board_posts))
+        .route("/boards/:slug/feed.xml", get(handlers::board_feed))
+        .route("/tags/:tag", get(handlers::tag_posts))
         .route(
             "/api/posts",
             get(handlers::api_list_posts).post(handlers::create_post),
         )
         .route(
             "/api/posts/:id",
-            put(handlers::update_post).delete(handlers::delete_post),
+            get(handlers::api_get_post)
+                .put(handlers::update_post)
+                .delete(handlers::delete_post),
+        )
+        .route("/api/posts/:id/restore", post(handlers::restore_post))
+        .route("/api/posts/:id/replies", get(handlers::api_list_replies))
+        .route(
+            "/api/boards",
+            get(handlers::api_list_boards).post(handlers::api_create_board),
+        )
+        .route(
+            "/api/boards/:id",
+            put(handlers::api_update_board).delete(handlers::api_delete_board),
+        )
+        .route(
+            "/api/boards/:id/members",
+            get(handlers::api_list_board_members).post(handlers::api_add_board_member),
+        )
+        .route(
+            "/api/boards/:id/members/:username",
+            delete(handlers::api_remove_board_member),
+        )
+        .route("/admin/auth-log", get(handlers::admin_auth_log))
+        .route("/admin/reports", get(handlers::admin_reports))
+        .route(
+            "/admin/reports/:id/resolve",
+            post(handlers::admin_resolve_report),
+        )
+        .route("/admin/posts/:id", delete(handlers::admin_delete_post))
+        .route(
+            "/admin/posts/:id/unpublish",
+            post(handlers::admin_unpublish_post),
         )
+        .route("/admin/posts/:id/pin", post(handlers::admin_toggle_pin))
+        .route("/admin/maintenance", post(handlers::admin_set_maintenance))
         .route("/health", get(handlers::health))
+        .route("/health/live", get(handlers::health_live))
+        .fallback(error_page::not_found)
+        .route_layer(middleware::from_fn(csrf::csrf_protection))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            error_page::html_errors,
+        ))
         .layer(CookieManagerLayer::new())
+        .layer(middleware::from_fn(security_headers::security_headers))
+        .layer(middleware::from_fn(request_id::request_id))
+        .with_state(state)
 }